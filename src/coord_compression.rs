@@ -1,11 +1,12 @@
 #[snippet = "CoordCompression"]
-struct CoordCompression {
+pub struct CoordCompression {
     comp: std::collections::HashMap<i64,usize>,
     dcmp: std::collections::HashMap<usize,i64>,
 }
 #[snippet = "CoordCompression"]
+#[allow(dead_code)]
 impl CoordCompression {
-    fn new(xs: &[i64], start: usize) -> CoordCompression {
+    pub fn new(xs: &[i64], start: usize) -> CoordCompression {
         let mut xs = xs.to_owned();
         xs.sort();
         let mut comp = std::collections::HashMap::new();
@@ -22,12 +23,16 @@ impl CoordCompression {
             dcmp,
         }
     }
-    fn compress(&self, x: i64) -> usize {
+    pub fn compress(&self, x: i64) -> usize {
         *self.comp.get(&x).unwrap()
     }
-    fn decompress(&self, x: usize) -> i64 {
+    pub fn decompress(&self, x: usize) -> i64 {
         *self.dcmp.get(&x).unwrap()
     }
+    #[doc = "number of distinct values"]
+    pub fn len(&self) -> usize {
+        self.comp.len()
+    }
 }
 #[test]
 fn test_coord_compression() {
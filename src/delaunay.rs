@@ -0,0 +1,298 @@
+use crate::geometry::{convex_cut, Point, Vector2D};
+use crate::total::Total;
+use crate::union_find::UnionFind;
+
+// Guibas-Stolfi divide and conquer on a quad-edge structure (KACTL FastDelaunay).
+// edge e belongs to quad e/4. rot(e) is the dual edge, e^2 the reversed edge.
+#[snippet = "Delaunay"]
+struct QuadEdge<'a> {
+    ps: &'a [Vector2D],
+    o: Vec<usize>,
+    p: Vec<usize>,
+    alive: Vec<bool>,
+}
+
+#[snippet = "Delaunay"]
+impl<'a> QuadEdge<'a> {
+    fn rot(e: usize) -> usize {
+        (e & !3) | ((e + 1) & 3)
+    }
+    fn f(&self, e: usize) -> usize {
+        self.p[e ^ 2]
+    }
+    fn prev(&self, e: usize) -> usize {
+        Self::rot(self.o[Self::rot(e)])
+    }
+    // around the left face
+    fn next(&self, e: usize) -> usize {
+        self.prev(e ^ 2)
+    }
+    fn make_edge(&mut self, orig: usize, dest: usize) -> usize {
+        let q = self.o.len();
+        self.o.extend_from_slice(&[q, q + 3, q + 2, q + 1]);
+        self.p.extend_from_slice(&[orig, !0, dest, !0]);
+        self.alive.push(true);
+        q
+    }
+    fn splice(&mut self, a: usize, b: usize) {
+        let x = Self::rot(self.o[a]);
+        let y = Self::rot(self.o[b]);
+        self.o.swap(x, y);
+        self.o.swap(a, b);
+    }
+    fn connect(&mut self, a: usize, b: usize) -> usize {
+        let q = self.make_edge(self.f(a), self.p[b]);
+        let na = self.next(a);
+        self.splice(q, na);
+        self.splice(q ^ 2, b);
+        q
+    }
+    fn delete(&mut self, e: usize) {
+        let pe = self.prev(e);
+        self.splice(e, pe);
+        let pr = self.prev(e ^ 2);
+        self.splice(e ^ 2, pr);
+        self.alive[e >> 2] = false;
+    }
+    fn cross(&self, p: usize, a: usize, b: usize) -> f64 {
+        (self.ps[a] - self.ps[p]).det(self.ps[b] - self.ps[p])
+    }
+    // is p strictly inside the circumcircle of a, b, c ?
+    fn circ(&self, p: usize, a: usize, b: usize, c: usize) -> bool {
+        let q = self.ps[p];
+        let (a, b, c) = (self.ps[a] - q, self.ps[b] - q, self.ps[c] - q);
+        a.dot(a) * b.det(c) + b.dot(b) * c.det(a) + c.dot(c) * a.det(b) > 0.0
+    }
+    fn valid(&self, e: usize, base: usize) -> bool {
+        self.cross(self.f(e), self.f(base), self.p[base]) > 0.0
+    }
+    fn rec(&mut self, s: &[usize]) -> (usize, usize) {
+        if s.len() <= 3 {
+            let a = self.make_edge(s[0], s[1]);
+            if s.len() == 2 {
+                return (a, a ^ 2);
+            }
+            let b = self.make_edge(s[1], s[2]);
+            self.splice(a ^ 2, b);
+            let side = self.cross(s[0], s[1], s[2]);
+            if side == 0.0 {
+                return (a, b ^ 2);
+            }
+            let c = self.connect(b, a);
+            return if side < 0.0 { (c ^ 2, c) } else { (a, b ^ 2) };
+        }
+        let half = s.len() / 2;
+        let (mut ra, mut a) = self.rec(&s[..s.len() - half]);
+        let (mut b, mut rb) = self.rec(&s[s.len() - half..]);
+        loop {
+            if self.cross(self.p[b], self.f(a), self.p[a]) < 0.0 {
+                a = self.next(a);
+            } else if self.cross(self.p[a], self.f(b), self.p[b]) > 0.0 {
+                b = self.o[b ^ 2];
+            } else {
+                break;
+            }
+        }
+        let mut base = self.connect(b ^ 2, a);
+        if self.p[a] == self.p[ra] {
+            ra = base ^ 2;
+        }
+        if self.p[b] == self.p[rb] {
+            rb = base;
+        }
+        loop {
+            let mut lc = self.o[base ^ 2];
+            if self.valid(lc, base) {
+                while self.circ(self.f(self.o[lc]), self.f(base), self.p[base], self.f(lc)) {
+                    let t = self.o[lc];
+                    self.delete(lc);
+                    lc = t;
+                }
+            }
+            let mut rc = self.prev(base);
+            if self.valid(rc, base) {
+                while self.circ(self.f(self.prev(rc)), self.f(base), self.p[base], self.f(rc)) {
+                    let t = self.prev(rc);
+                    self.delete(rc);
+                    rc = t;
+                }
+            }
+            let (vl, vr) = (self.valid(lc, base), self.valid(rc, base));
+            if !vl && !vr {
+                break;
+            }
+            if !vl || (vr && self.circ(self.f(rc), self.p[rc], self.f(lc), self.p[lc])) {
+                base = self.connect(rc, base ^ 2);
+            } else {
+                base = self.connect(base ^ 2, lc ^ 2);
+            }
+        }
+        (ra, rb)
+    }
+}
+
+#[snippet = "Delaunay"]
+#[derive(Debug, Clone)]
+pub struct Delaunay {
+    /// counter clockwise
+    pub triangles: Vec<[usize; 3]>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+#[snippet = "Delaunay"]
+impl Delaunay {
+    #[doc = "ps must be distinct. exact for integer coordinates up to ~5000. O(n log n)"]
+    pub fn new(ps: &[Vector2D]) -> Delaunay {
+        let mut idx: Vec<usize> = (0..ps.len()).collect();
+        idx.sort_by_key(|&i| Total((ps[i].0, ps[i].1)));
+        let mut qe = QuadEdge { ps: ps, o: vec![], p: vec![], alive: vec![] };
+        if ps.len() >= 2 {
+            qe.rec(&idx);
+        }
+        let mut edges = vec![];
+        let mut triangles = vec![];
+        for q in 0..qe.alive.len() {
+            if !qe.alive[q] {
+                continue;
+            }
+            let e = 4 * q;
+            edges.push((qe.p[e], qe.f(e)));
+            for &e in &[e, e ^ 2] {
+                let e1 = qe.next(e);
+                let e2 = qe.next(e1);
+                // each triangle once, from its smallest edge
+                if qe.next(e2) == e && e < e1 && e < e2 && qe.cross(qe.p[e], qe.p[e1], qe.p[e2]) > 0.0 {
+                    triangles.push([qe.p[e], qe.p[e1], qe.p[e2]]);
+                }
+            }
+        }
+        Delaunay { triangles: triangles, edges: edges }
+    }
+}
+
+#[snippet = "euclidean_mst"]
+#[doc = "edges of the euclidean minimum spanning tree. O(n log n)"]
+pub fn euclidean_mst(ps: &[Vector2D]) -> Vec<(usize, usize)> {
+    let mut es = Delaunay::new(ps).edges;
+    es.sort_by_key(|&(u, v)| Total((ps[u] - ps[v]).len()));
+    let mut uf = UnionFind::new(ps.len());
+    let mut res = vec![];
+    for (u, v) in es {
+        if uf.merge(u, v) {
+            res.push((u, v));
+        }
+    }
+    res
+}
+
+#[snippet = "voronoi"]
+#[doc = "voronoi cell of each point clipped to the box [lo, hi]. counter clockwise. O(sum of deg * cell size)"]
+pub fn voronoi(ps: &[Vector2D], delaunay: &Delaunay, lo: Vector2D, hi: Vector2D) -> Vec<Vec<Vector2D>> {
+    let n = ps.len();
+    let mut adj = vec![vec![]; n];
+    for &(u, v) in &delaunay.edges {
+        adj[u].push(v);
+        adj[v].push(u);
+    }
+    let bx = vec![Point(lo.0, lo.1), Point(hi.0, lo.1), Point(hi.0, hi.1), Point(lo.0, hi.1)];
+    (0..n)
+        .map(|i| {
+            let mut cell = bx.clone();
+            for &j in &adj[i] {
+                // keep the side closer to ps[i]
+                let m = (ps[i] + ps[j]) / 2.0;
+                let d = (ps[i] - ps[j]).normal();
+                cell = convex_cut(&cell, m.into(), (m + d).into());
+            }
+            cell.into_iter().map(|p| Vector2D(p.0, p.1)).collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+fn random_points(n: usize, m: u64, seed: u64) -> Vec<Vector2D> {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::with_seed(seed);
+    let mut set = std::collections::HashSet::new();
+    let mut ps = vec![];
+    while ps.len() < n {
+        let (x, y) = (rng.rand(m) as i64, rng.rand(m) as i64);
+        if set.insert((x, y)) {
+            ps.push(Vector2D(x as f64, y as f64));
+        }
+    }
+    ps
+}
+
+#[test]
+fn test_delaunay() {
+    use crate::geometry::{convex_hull, point_in_polygon, Circle, Containment};
+    for seed in 1..30 {
+        let n = seed as usize * 5;
+        let ps = random_points(n, 100, seed);
+        let d = Delaunay::new(&ps);
+        let vs: Vec<Point<f64>> = ps.iter().map(|&p| p.into()).collect();
+        let hull: Vec<Point<f64>> = convex_hull(&vs).into_iter().map(|i| vs[i]).collect();
+        let h = vs.iter().filter(|&&v| point_in_polygon(&hull, v) == Containment::Boundary).count();
+        if hull.len() < 3 {
+            assert!(d.triangles.is_empty());
+        } else {
+            assert_eq!(d.triangles.len(), 2 * n - 2 - h);
+        }
+        assert_eq!(d.edges.len() + 1, n + d.triangles.len());
+        for t in &d.triangles {
+            assert!((ps[t[1]] - ps[t[0]]).det(ps[t[2]] - ps[t[0]]) > 0.0);
+            let c = Circle::circumcircle(ps[t[0]], ps[t[1]], ps[t[2]]).unwrap();
+            for &p in &ps {
+                assert!((p - c.c).len() > c.r - 1e-6);
+            }
+        }
+    }
+    // collinear
+    let ps: Vec<Vector2D> = (0..5).map(|i| Vector2D(i as f64, 2.0 * i as f64)).collect();
+    let d = Delaunay::new(&ps);
+    assert!(d.triangles.is_empty());
+    assert_eq!(d.edges.len(), 4);
+    assert_eq!(Delaunay::new(&ps[..1]).edges.len(), 0);
+}
+
+#[test]
+fn test_euclidean_mst() {
+    for seed in 1..10 {
+        let ps = random_points(100, 1000, seed);
+        let n = ps.len();
+        let mst = euclidean_mst(&ps);
+        assert_eq!(mst.len(), n - 1);
+        let total: f64 = mst.iter().map(|&(u, v)| (ps[u] - ps[v]).len()).sum();
+        // prim
+        let mut used = vec![false; n];
+        let mut dist = vec![std::f64::MAX; n];
+        dist[0] = 0.0;
+        let mut expected = 0.0;
+        for _ in 0..n {
+            let u = (0..n).filter(|&u| !used[u]).min_by_key(|&u| Total(dist[u])).unwrap();
+            used[u] = true;
+            expected += dist[u];
+            for v in 0..n {
+                dist[v] = dist[v].min((ps[u] - ps[v]).len());
+            }
+        }
+        assert!((total - expected).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_voronoi() {
+    use crate::geometry::{point_in_polygon, Containment};
+    use crate::xorshift::Xorshift;
+    let ps = random_points(50, 100, 7);
+    let d = Delaunay::new(&ps);
+    let cells = voronoi(&ps, &d, Vector2D(-10.0, -10.0), Vector2D(110.0, 110.0));
+    let mut rng = Xorshift::with_seed(8);
+    for _ in 0..1000 {
+        let q = Vector2D(rng.randf() * 100.0, rng.randf() * 100.0);
+        let i = (0..ps.len()).min_by_key(|&i| Total((ps[i] - q).len())).unwrap();
+        let cell: Vec<Point<f64>> = cells[i].iter().map(|&p| p.into()).collect();
+        assert_ne!(point_in_polygon(&cell, q.into()), Containment::Outside);
+    }
+}
@@ -0,0 +1,314 @@
+use crate::seg_lazy::MapMonoid;
+use crate::seg::Monoid;
+
+// Both trees cover [lo, lo + 2^log) and allocate nodes on first touch.
+// node 0 is the null node and node 1 the root.
+
+#[snippet = "DynamicSEG"]
+#[allow(dead_code)]
+/// Segment tree over i64 indices in [lo, hi) with nodes allocated on demand. |index| <= 1e18
+pub struct DynamicSEG<M: Monoid> {
+    lo: i64,
+    log: usize,
+    buf: Vec<M::T>,
+    ch: Vec<[usize; 2]>,
+}
+
+#[snippet = "DynamicSEG"]
+#[allow(dead_code)]
+impl<M: Monoid> DynamicSEG<M> {
+    #[doc = "all values are id"]
+    pub fn new(lo: i64, hi: i64) -> DynamicSEG<M> {
+        let mut log = 0;
+        while (1i64 << log) < hi - lo {
+            log += 1;
+        }
+        DynamicSEG {
+            lo: lo,
+            log: log,
+            buf: vec![M::id(), M::id()],
+            ch: vec![[0, 0]; 2],
+        }
+    }
+    #[doc = "number of allocated nodes"]
+    pub fn len(&self) -> usize {
+        self.buf.len() - 1
+    }
+    #[doc = "O(log(hi - lo)) time and new nodes"]
+    pub fn update(&mut self, k: i64, x: M::T) {
+        let k = (k - self.lo) as u64;
+        let mut path = Vec::with_capacity(self.log);
+        let mut v = 1;
+        for d in (0..self.log).rev() {
+            let b = (k >> d & 1) as usize;
+            if self.ch[v][b] == 0 {
+                self.ch[v][b] = self.buf.len();
+                self.buf.push(M::id());
+                self.ch.push([0, 0]);
+            }
+            path.push(v);
+            v = self.ch[v][b];
+        }
+        self.buf[v] = x;
+        for &v in path.iter().rev() {
+            let [c0, c1] = self.ch[v];
+            self.buf[v] = M::op(&self.buf[c0], &self.buf[c1]);
+        }
+    }
+    pub fn get(&self, k: i64) -> M::T {
+        let k = (k - self.lo) as u64;
+        let mut v = 1;
+        for d in (0..self.log).rev() {
+            v = self.ch[v][(k >> d & 1) as usize];
+            if v == 0 {
+                return M::id();
+            }
+        }
+        self.buf[v].clone()
+    }
+    fn do_query(&self, v: usize, d: usize, l: u64, a: u64, b: u64) -> M::T {
+        let r = l + (1 << d);
+        if v == 0 || r <= a || b <= l {
+            return M::id();
+        }
+        if a <= l && r <= b {
+            return self.buf[v].clone();
+        }
+        let m = l + (1 << (d - 1));
+        let [c0, c1] = self.ch[v];
+        M::op(&self.do_query(c0, d - 1, l, a, b), &self.do_query(c1, d - 1, m, a, b))
+    }
+    #[doc = "[a, b). O(log(hi - lo))"]
+    pub fn query(&self, a: i64, b: i64) -> M::T {
+        if a >= b {
+            return M::id();
+        }
+        self.do_query(1, self.log, 0, (a - self.lo) as u64, (b - self.lo) as u64)
+    }
+}
+
+#[snippet = "DynamicLazySEG"]
+#[allow(dead_code)]
+/// Lazy segment tree over i64 indices in [lo, hi) with nodes allocated on demand. |index| <= 1e18
+pub struct DynamicLazySEG<A: MapMonoid> {
+    lo: i64,
+    log: usize,
+    // pw[d]: value of an untouched node covering 2^d indices
+    pw: Vec<<A::M as Monoid>::T>,
+    buf: Vec<<A::M as Monoid>::T>,
+    lazy: Vec<A::F>,
+    ch: Vec<[usize; 2]>,
+}
+
+#[snippet = "DynamicLazySEG"]
+#[allow(dead_code)]
+impl<A: MapMonoid> DynamicLazySEG<A> {
+    #[doc = "every index holds x"]
+    pub fn new(lo: i64, hi: i64, x: <A::M as Monoid>::T) -> DynamicLazySEG<A> {
+        let mut pw = vec![x];
+        while (1i64 << (pw.len() - 1)) < hi - lo {
+            let y = pw[pw.len() - 1].clone();
+            pw.push(<A::M as Monoid>::op(&y, &y));
+        }
+        let log = pw.len() - 1;
+        let root = pw[log].clone();
+        DynamicLazySEG {
+            lo: lo,
+            log: log,
+            pw: pw,
+            buf: vec![<A::M as Monoid>::id(), root],
+            lazy: vec![A::id_map(), A::id_map()],
+            ch: vec![[0, 0]; 2],
+        }
+    }
+    #[doc = "number of allocated nodes"]
+    pub fn len(&self) -> usize {
+        self.buf.len() - 1
+    }
+    fn all_apply(&mut self, v: usize, f: &A::F) {
+        self.buf[v] = A::apply(f, &self.buf[v]);
+        self.lazy[v] = A::compose(f, &self.lazy[v]);
+    }
+    // allocate the children of v (covering 2^d indices) and hand down its lazy value
+    fn push(&mut self, v: usize, d: usize) {
+        for b in 0..2 {
+            if self.ch[v][b] == 0 {
+                self.ch[v][b] = self.buf.len();
+                self.buf.push(self.pw[d - 1].clone());
+                self.lazy.push(A::id_map());
+                self.ch.push([0, 0]);
+            }
+        }
+        let f = std::mem::replace(&mut self.lazy[v], A::id_map());
+        let [c0, c1] = self.ch[v];
+        self.all_apply(c0, &f);
+        self.all_apply(c1, &f);
+    }
+    fn do_update(&mut self, v: usize, d: usize, l: u64, a: u64, b: u64, f: &A::F) {
+        let r = l + (1 << d);
+        if r <= a || b <= l {
+            return;
+        }
+        if a <= l && r <= b {
+            self.all_apply(v, f);
+            return;
+        }
+        self.push(v, d);
+        let m = l + (1 << (d - 1));
+        let [c0, c1] = self.ch[v];
+        self.do_update(c0, d - 1, l, a, b, f);
+        self.do_update(c1, d - 1, m, a, b, f);
+        self.buf[v] = <A::M as Monoid>::op(&self.buf[c0], &self.buf[c1]);
+    }
+    #[doc = "apply f to [a, b). O(log(hi - lo)) time and new nodes"]
+    pub fn update(&mut self, a: i64, b: i64, f: A::F) {
+        if a < b {
+            let log = self.log;
+            self.do_update(1, log, 0, (a - self.lo) as u64, (b - self.lo) as u64, &f);
+        }
+    }
+    fn do_query(&mut self, v: usize, d: usize, l: u64, a: u64, b: u64) -> <A::M as Monoid>::T {
+        let r = l + (1 << d);
+        if r <= a || b <= l {
+            return <A::M as Monoid>::id();
+        }
+        if a <= l && r <= b {
+            return self.buf[v].clone();
+        }
+        self.push(v, d);
+        let m = l + (1 << (d - 1));
+        let [c0, c1] = self.ch[v];
+        let x = self.do_query(c0, d - 1, l, a, b);
+        let y = self.do_query(c1, d - 1, m, a, b);
+        <A::M as Monoid>::op(&x, &y)
+    }
+    #[doc = "[a, b). O(log(hi - lo))"]
+    pub fn query(&mut self, a: i64, b: i64) -> <A::M as Monoid>::T {
+        if a >= b {
+            return <A::M as Monoid>::id();
+        }
+        let log = self.log;
+        self.do_query(1, log, 0, (a - self.lo) as u64, (b - self.lo) as u64)
+    }
+    pub fn set(&mut self, k: i64, x: <A::M as Monoid>::T) {
+        let k = (k - self.lo) as u64;
+        let mut path = Vec::with_capacity(self.log);
+        let mut v = 1;
+        for d in (1..self.log + 1).rev() {
+            self.push(v, d);
+            path.push(v);
+            v = self.ch[v][(k >> (d - 1) & 1) as usize];
+        }
+        self.buf[v] = x;
+        for &v in path.iter().rev() {
+            let [c0, c1] = self.ch[v];
+            self.buf[v] = <A::M as Monoid>::op(&self.buf[c0], &self.buf[c1]);
+        }
+    }
+}
+
+#[test]
+fn test_dynamic_seg() {
+    use crate::seg::SUM;
+    use crate::xorshift::Xorshift;
+    use std::collections::BTreeMap;
+    let mut rng = Xorshift::new();
+    let (lo, hi) = (-1_000_000_000_000_000_000i64, 1_000_000_000_000_000_000i64);
+    let mut seg: DynamicSEG<SUM> = DynamicSEG::new(lo, hi);
+    let mut brute = BTreeMap::new();
+    // a few distinct keys so that queries hit them
+    let keys: Vec<i64> = (0..30).map(|_| rng.rand(2_000_000_000_000_000_000) as i64 - 1_000_000_000_000_000_000).collect();
+    for _ in 0..2000 {
+        let k = keys[rng.rand(30) as usize];
+        if rng.rand(2) == 0 {
+            let x = rng.rand(1000) as i64;
+            seg.update(k, x);
+            brute.insert(k, x);
+            assert_eq!(seg.get(k), x);
+        } else {
+            let k2 = keys[rng.rand(30) as usize];
+            let (a, b) = (k.min(k2), k.max(k2) + 1);
+            assert_eq!(seg.query(a, b), brute.range(a..b).map(|(_, &x)| x).sum::<i64>());
+        }
+    }
+    assert!(seg.len() <= 30 * 61 + 1);
+    assert_eq!(seg.query(lo, hi), brute.values().sum::<i64>());
+    assert_eq!(seg.get(hi - 1), brute.get(&(hi - 1)).cloned().unwrap_or(0));
+}
+
+#[test]
+fn test_dynamic_lazy_seg() {
+    use crate::xorshift::Xorshift;
+    // (sum, length) with range add
+    struct SUM_LEN;
+    impl Monoid for SUM_LEN {
+        type T = (i64, i64);
+        fn id() -> (i64, i64) {
+            (0, 0)
+        }
+        fn op(a: &(i64, i64), b: &(i64, i64)) -> (i64, i64) {
+            (a.0 + b.0, a.1 + b.1)
+        }
+    }
+    struct ADD;
+    impl MapMonoid for ADD {
+        type M = SUM_LEN;
+        type F = i64;
+        fn id_map() -> i64 {
+            0
+        }
+        fn compose(f: &i64, g: &i64) -> i64 {
+            f + g
+        }
+        fn apply(f: &i64, x: &(i64, i64)) -> (i64, i64) {
+            (x.0 + f * x.1, x.1)
+        }
+    }
+    let mut rng = Xorshift::new();
+    let n = 100;
+    let mut v = vec![1i64; n];
+    let mut seg: DynamicLazySEG<ADD> = DynamicLazySEG::new(-50, 50, (1, 1));
+    for _ in 0..2000 {
+        let a = rng.rand(n as u64 + 1) as usize;
+        let b = rng.rand(n as u64 + 1) as usize;
+        let (a, b) = (a.min(b), a.max(b));
+        match rng.rand(3) {
+            0 => {
+                let x = rng.rand(21) as i64 - 10;
+                seg.update(a as i64 - 50, b as i64 - 50, x);
+                for i in a..b {
+                    v[i] += x;
+                }
+            }
+            1 => {
+                if a < n {
+                    let x = rng.rand(100) as i64;
+                    seg.set(a as i64 - 50, (x, 1));
+                    v[a] = x;
+                }
+            }
+            _ => {
+                let s: i64 = v[a..b].iter().sum();
+                assert_eq!(seg.query(a as i64 - 50, b as i64 - 50), (s, (b - a) as i64));
+            }
+        }
+    }
+
+    // huge range with few updates. about 100 adds over lengths below 2e15 keep the sums in i64
+    let m = 1_000_000_000_000_000i64;
+    let mut seg: DynamicLazySEG<ADD> = DynamicLazySEG::new(-m, m, (0, 1));
+    let mut ups = vec![];
+    for _ in 0..200 {
+        let a = rng.rand(2 * m as u64) as i64 - m;
+        let b = rng.rand(2 * m as u64) as i64 - m;
+        let (a, b) = (a.min(b), a.max(b));
+        if rng.rand(2) == 0 {
+            seg.update(a, b, 1);
+            ups.push((a, b));
+        } else {
+            let s: i64 = ups.iter().map(|&(c, d)| (d.min(b) - c.max(a)).max(0)).sum();
+            assert_eq!(seg.query(a, b), (s, b - a));
+        }
+    }
+    assert!(seg.len() <= 4 * 200 * 52);
+}
@@ -150,6 +150,27 @@ impl <T: Clone + Default + std::ops::AddAssign> BIT<T> {
     }
 }
 
+#[snippet = "BIT"]
+#[allow(dead_code)]
+impl <T: Clone + Default + std::ops::AddAssign + std::ops::SubAssign + PartialOrd> BIT<T> {
+    #[doc = "min i >= 1 s.t. sum(i) >= w, so 1 when w <= 0. all elements must be non-negative. O(log N)"]
+    pub fn lower_bound(&self, w: T) -> usize {
+        let n = self.buf.len() - 1;
+        let mut w = w;
+        let mut x = 0;
+        let mut k = 1;
+        while k * 2 <= n { k *= 2; }
+        while k > 0 {
+            if x + k <= n && self.buf[x + k] < w {
+                w -= self.buf[x + k].clone();
+                x += k;
+            }
+            k >>= 1;
+        }
+        x + 1
+    }
+}
+
 #[test]
 fn test_bit_vs_cumsum() {
     use rand::{Rng, SeedableRng, StdRng};
@@ -185,4 +206,216 @@ fn test_bit() {
     assert_eq!(bit.sum(2), 3);
     assert_eq!(bit.sum(3), 6);
     assert_eq!(bit.sum(4), 10);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_bit_lower_bound() {
+    let mut bit = BIT::new(5);
+    bit.add(1, 1);
+    bit.add(3, 2);
+    bit.add(5, 1);
+    assert_eq!(bit.lower_bound(0), 1);
+    assert_eq!(bit.lower_bound(1), 1);
+    assert_eq!(bit.lower_bound(2), 3);
+    assert_eq!(bit.lower_bound(3), 3);
+    assert_eq!(bit.lower_bound(4), 5);
+    assert_eq!(bit.lower_bound(5), 6);
+}
+
+#[snippet = "BIT2D"]
+#[allow(dead_code)]
+/// 2D Binary Indexed Tree. 1-indexed like BIT
+pub struct BIT2D<T> {
+    h: usize,
+    w: usize,
+    buf: Vec<Vec<T>>,
+}
+
+#[snippet = "BIT2D"]
+#[allow(dead_code)]
+impl <T: Clone + Default + std::ops::AddAssign> BIT2D<T> {
+    pub fn new(h: usize, w: usize) -> BIT2D<T> {
+        BIT2D {
+            h: h,
+            w: w,
+            buf: vec![vec![T::default(); w+1]; h+1],
+        }
+    }
+
+    #[doc = "sum of [1,i] x [1,j]. O(log h log w)"]
+    pub fn sum(&self, i: usize, j: usize) -> T {
+        let mut s = T::default();
+        let mut i = i;
+        while i > 0 {
+            let mut j = j;
+            while j > 0 {
+                s += self.buf[i][j].clone();
+                j &= j - 1;
+            }
+            i &= i - 1;
+        }
+        s
+    }
+
+    #[doc = "O(log h log w)"]
+    pub fn add(&mut self, i: usize, j: usize, x: T) {
+        let mut i = i;
+        while i <= self.h {
+            let mut j = j;
+            while j <= self.w {
+                self.buf[i][j] += x.clone();
+                j += j & j.wrapping_neg();
+            }
+            i += i & i.wrapping_neg();
+        }
+    }
+}
+
+#[snippet = "BIT2D"]
+#[allow(dead_code)]
+impl <T: Clone + Default + std::ops::AddAssign + std::ops::SubAssign> BIT2D<T> {
+    #[doc = "sum of [i0,i1] x [j0,j1]. O(log h log w)"]
+    pub fn query(&self, i0: usize, i1: usize, j0: usize, j1: usize) -> T {
+        let mut s = self.sum(i1, j1);
+        s -= self.sum(i0 - 1, j1);
+        s -= self.sum(i1, j0 - 1);
+        s += self.sum(i0 - 1, j0 - 1);
+        s
+    }
+}
+
+#[test]
+fn test_bit2d() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    let (h, w) = (13, 7);
+    let mut bit = BIT2D::new(h, w);
+    let mut v = vec![vec![0i64; w+1]; h+1];
+    for _ in 0..1000 {
+        let (i, j) = (rng.rand(h as u64) as usize + 1, rng.rand(w as u64) as usize + 1);
+        let x = rng.rand(100) as i64 - 50;
+        bit.add(i, j, x);
+        v[i][j] += x;
+        let (a, b) = (rng.rand(h as u64) as usize + 1, rng.rand(h as u64) as usize + 1);
+        let (c, d) = (rng.rand(w as u64) as usize + 1, rng.rand(w as u64) as usize + 1);
+        let (i0, i1, j0, j1) = (a.min(b), a.max(b), c.min(d), c.max(d));
+        let s: i64 = (i0..i1+1).map(|i| v[i][j0..j1+1].iter().sum::<i64>()).sum();
+        assert_eq!(bit.query(i0, i1, j0, j1), s);
+        assert_eq!(bit.sum(h, w), v.iter().map(|r| r.iter().sum::<i64>()).sum::<i64>());
+    }
+}
+
+#[snippet = "BIT2DOffline"]
+#[allow(dead_code)]
+/// 2D Binary Indexed Tree on sparse points known in advance. O(n log n) memory
+pub struct BIT2DOffline<T> {
+    cc: crate::coord_compression::CoordCompression,
+    ys: Vec<Vec<i64>>,
+    buf: Vec<Vec<T>>,
+}
+
+#[snippet = "BIT2DOffline"]
+#[allow(dead_code)]
+impl <T: Clone + Default + std::ops::AddAssign> BIT2DOffline<T> {
+    #[doc = "ps: every point that add will be called with. O(n log^2 n)"]
+    pub fn new(ps: &[(i64, i64)]) -> BIT2DOffline<T> {
+        let xs: Vec<i64> = ps.iter().map(|p| p.0).collect();
+        let cc = crate::coord_compression::CoordCompression::new(&xs, 1);
+        let n = cc.len();
+        let mut ys = vec![vec![]; n+1];
+        for &(x, y) in ps {
+            let mut i = cc.compress(x);
+            while i <= n {
+                ys[i].push(y);
+                i += i & i.wrapping_neg();
+            }
+        }
+        for v in &mut ys {
+            v.sort();
+            v.dedup();
+        }
+        let buf = ys.iter().map(|v| vec![T::default(); v.len()+1]).collect();
+        BIT2DOffline {
+            cc: cc,
+            ys: ys,
+            buf: buf,
+        }
+    }
+
+    #[doc = "(x, y) must be one of the points given to new. O(log^2 n)"]
+    pub fn add(&mut self, x: i64, y: i64, v: T) {
+        let mut i = self.cc.compress(x);
+        while i < self.buf.len() {
+            let mut j = self.ys[i].binary_search(&y).unwrap() + 1;
+            while j < self.buf[i].len() {
+                self.buf[i][j] += v.clone();
+                j += j & j.wrapping_neg();
+            }
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    #[doc = "sum of points with px < x and py < y. O(log^2 n)"]
+    pub fn sum(&self, x: i64, y: i64) -> T {
+        let mut s = T::default();
+        // number of distinct xs less than x
+        let (mut lo, mut hi) = (0, self.cc.len());
+        while lo < hi {
+            let mid = (lo + hi + 1) / 2;
+            if self.cc.decompress(mid) < x {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        let mut i = lo;
+        while i > 0 {
+            let mut j = match self.ys[i].binary_search(&y) { Ok(j) | Err(j) => j };
+            while j > 0 {
+                s += self.buf[i][j].clone();
+                j &= j - 1;
+            }
+            i &= i - 1;
+        }
+        s
+    }
+}
+
+#[snippet = "BIT2DOffline"]
+#[allow(dead_code)]
+impl <T: Clone + Default + std::ops::AddAssign + std::ops::SubAssign> BIT2DOffline<T> {
+    #[doc = "sum of [x0,x1) x [y0,y1). O(log^2 n)"]
+    pub fn query(&self, x0: i64, x1: i64, y0: i64, y1: i64) -> T {
+        let mut s = self.sum(x1, y1);
+        s -= self.sum(x0, y1);
+        s -= self.sum(x1, y0);
+        s += self.sum(x0, y0);
+        s
+    }
+}
+
+#[test]
+fn test_bit2d_offline() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    let m = 1_000_000_000;
+    // few distinct coordinates so that queries are not always empty
+    let cs: Vec<i64> = (0..10).map(|_| rng.rand(2 * m + 1) as i64 - m as i64).collect();
+    let ps: Vec<(i64, i64)> = (0..50).map(|_| (cs[rng.rand(10) as usize], cs[rng.rand(10) as usize])).collect();
+    let mut bit = BIT2DOffline::new(&ps);
+    let mut v = vec![0i64; ps.len()];
+    for _ in 0..1000 {
+        let k = rng.rand(ps.len() as u64) as usize;
+        let x = rng.rand(100) as i64 - 50;
+        bit.add(ps[k].0, ps[k].1, x);
+        v[k] += x;
+        let (a, b) = (cs[rng.rand(10) as usize], cs[rng.rand(10) as usize] + rng.rand(2) as i64);
+        let (c, d) = (cs[rng.rand(10) as usize], cs[rng.rand(10) as usize] + rng.rand(2) as i64);
+        let (x0, x1, y0, y1) = (a.min(b), a.max(b), c.min(d), c.max(d));
+        let s: i64 = (0..ps.len())
+            .filter(|&i| x0 <= ps[i].0 && ps[i].0 < x1 && y0 <= ps[i].1 && ps[i].1 < y1)
+            .map(|i| v[i])
+            .sum();
+        assert_eq!(bit.query(x0, x1, y0, y1), s);
+    }
+}
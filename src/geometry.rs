@@ -1,17 +1,31 @@
 /// https://github.com/hatoo/competitive-rust-snippets
-
 use std;
 
+#[snippet = "Vector2D"]
+pub const EPS: f64 = 1e-10;
+
+#[snippet = "Vector2D"]
+#[doc = "sign with EPS tolerance"]
+pub fn sgn(x: f64) -> i32 {
+    if x < -EPS {
+        -1
+    } else if x > EPS {
+        1
+    } else {
+        0
+    }
+}
+
 #[snippet = "Vector2D"]
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[allow(dead_code)]
-pub struct Vector2D(f64, f64);
+pub struct Vector2D(pub f64, pub f64);
 
 #[snippet = "Vector2D"]
 impl Vector2D {
     pub fn add(a: f64, b: f64) -> f64 {
         let c = a + b;
-        if c.abs() < 1e-10 {
+        if c.abs() < EPS {
             0.0
         } else {
             c
@@ -73,22 +87,529 @@ impl std::ops::Div<f64> for Vector2D {
     }
 }
 
+#[snippet = "Line"]
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// infinite line through .0 and .1
+pub struct Line(pub Vector2D, pub Vector2D);
+
+#[snippet = "Line"]
+#[allow(dead_code)]
+impl Line {
+    pub fn dir(&self) -> Vector2D {
+        self.1 - self.0
+    }
+    pub fn projection(&self, p: Vector2D) -> Vector2D {
+        let d = self.dir();
+        self.0 + d * ((p - self.0).dot(d) / d.dot(d))
+    }
+    pub fn reflection(&self, p: Vector2D) -> Vector2D {
+        self.projection(p) * 2.0 - p
+    }
+    pub fn distance(&self, p: Vector2D) -> f64 {
+        (self.dir().det(p - self.0) / self.dir().len()).abs()
+    }
+    pub fn is_parallel(&self, other: &Line) -> bool {
+        sgn(self.dir().det(other.dir())) == 0
+    }
+    pub fn is_orthogonal(&self, other: &Line) -> bool {
+        sgn(self.dir().dot(other.dir())) == 0
+    }
+    #[doc = "None if parallel"]
+    pub fn cross_point(&self, other: &Line) -> Option<Vector2D> {
+        let d = self.dir().det(other.dir());
+        if sgn(d) == 0 {
+            return None;
+        }
+        Some(self.0 + self.dir() * ((other.0 - self.0).det(other.dir()) / d))
+    }
+    #[doc = "in [0, pi/2]"]
+    pub fn angle(&self, other: &Line) -> f64 {
+        let (a, b) = (self.dir(), other.dir());
+        (a.dot(b).abs() / (a.len() * b.len())).min(1.0).acos()
+    }
+}
+
+#[snippet = "Line"]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment(pub Vector2D, pub Vector2D);
+
+#[snippet = "Line"]
+#[allow(dead_code)]
+impl Segment {
+    pub fn line(&self) -> Line {
+        Line(self.0, self.1)
+    }
+    #[doc = "touching counts"]
+    pub fn intersects(&self, other: &Segment) -> bool {
+        let (a, b, c, d) = (self.0, self.1, other.0, other.1);
+        if sgn((b - a).len()) == 0 {
+            return sgn(other.distance_point(a)) == 0;
+        }
+        if sgn((d - c).len()) == 0 {
+            return sgn(self.distance_point(c)) == 0;
+        }
+        let d1 = sgn((b - a).det(c - a));
+        let d2 = sgn((b - a).det(d - a));
+        let d3 = sgn((d - c).det(a - c));
+        let d4 = sgn((d - c).det(b - c));
+        if d1 == 0 && d2 == 0 {
+            // collinear. compare the projections onto the line
+            let v = b - a;
+            let (s0, s1) = (0.0f64, v.dot(v));
+            let (t0, t1) = ((c - a).dot(v), (d - a).dot(v));
+            let (t0, t1) = (t0.min(t1), t0.max(t1));
+            return sgn(s1.min(t1) - s0.max(t0)) >= 0;
+        }
+        d1 * d2 <= 0 && d3 * d4 <= 0
+    }
+    #[doc = "a common point. for overlapping collinear segments, one of the endpoints"]
+    pub fn cross_point(&self, other: &Segment) -> Option<Vector2D> {
+        if !self.intersects(other) {
+            return None;
+        }
+        if let Some(p) = self.line().cross_point(&other.line()) {
+            return Some(p);
+        }
+        for &p in &[other.0, other.1] {
+            if sgn(self.distance_point(p)) == 0 {
+                return Some(p);
+            }
+        }
+        Some(self.0)
+    }
+    pub fn distance_point(&self, p: Vector2D) -> f64 {
+        if sgn((p - self.0).dot(self.1 - self.0)) <= 0 {
+            (p - self.0).len()
+        } else if sgn((p - self.1).dot(self.0 - self.1)) < 0 {
+            (p - self.1).len()
+        } else {
+            self.line().distance(p)
+        }
+    }
+    pub fn distance(&self, other: &Segment) -> f64 {
+        if self.intersects(other) {
+            return 0.0;
+        }
+        self.distance_point(other.0)
+            .min(self.distance_point(other.1))
+            .min(other.distance_point(self.0))
+            .min(other.distance_point(self.1))
+    }
+}
+
+#[snippet = "half_plane_intersection"]
+#[derive(Debug, Clone, PartialEq)]
+pub enum HalfPlaneIntersection {
+    Empty,
+    Unbounded,
+    /// counter clockwise
+    Bounded(Vec<Vector2D>),
+}
+
+#[snippet = "half_plane_intersection"]
+#[doc = "intersection of the left sides of lines. Empty if it has no interior. predicates are division free, so exact for integer coordinates up to ~3000. O(n log n)"]
+pub fn half_plane_intersection(hs: &[Line]) -> HalfPlaneIntersection {
+    let half = |v: Vector2D| v.1 < 0.0 || (v.1 == 0.0 && v.0 < 0.0);
+    let mut hs = hs.to_vec();
+    hs.sort_by(|a, b| {
+        let (u, v) = (a.dir(), b.dir());
+        half(u)
+            .cmp(&half(v))
+            .then_with(|| 0.0.partial_cmp(&u.det(v)).unwrap())
+    });
+    let n = hs.len();
+
+    // unbounded iff all directions fit in a half circle
+    let same = |u: Vector2D, v: Vector2D| u.det(v) == 0.0 && u.dot(v) > 0.0;
+    if (0..n).all(|i| same(hs[i].dir(), hs[0].dir())) {
+        return HalfPlaneIntersection::Unbounded;
+    }
+    for i in 0..n {
+        let (a, b) = (hs[i].dir(), hs[(i + 1) % n].dir());
+        let c = a.det(b);
+        if c < 0.0 {
+            return HalfPlaneIntersection::Unbounded;
+        }
+        if c == 0.0 && a.dot(b) < 0.0 {
+            // the rest is a strip along a
+            let lo = hs
+                .iter()
+                .filter(|h| same(h.dir(), a))
+                .map(|h| Total(a.det(h.0)))
+                .max()
+                .unwrap();
+            let hi = hs
+                .iter()
+                .filter(|h| same(h.dir(), b))
+                .map(|h| Total(a.det(h.0)))
+                .min()
+                .unwrap();
+            return if lo < hi {
+                HalfPlaneIntersection::Unbounded
+            } else {
+                HalfPlaneIntersection::Empty
+            };
+        }
+    }
+
+    // is the crossing point of a and b not strictly on the left of h ?
+    let out = |h: &Line, a: &Line, b: &Line| {
+        let d = a.dir().det(b.dir());
+        let num = h.dir().det(a.0 - h.0) * d + (b.0 - a.0).det(b.dir()) * h.dir().det(a.dir());
+        num * d <= 0.0
+    };
+    let mut dq: std::collections::VecDeque<Line> = std::collections::VecDeque::new();
+    for h in hs {
+        while dq.len() > 1 && out(&h, &dq[dq.len() - 2], &dq[dq.len() - 1]) {
+            dq.pop_back();
+        }
+        while dq.len() > 1 && out(&h, &dq[0], &dq[1]) {
+            dq.pop_front();
+        }
+        if let Some(&last) = dq.back() {
+            let c = h.dir().det(last.dir());
+            if c == 0.0 && h.dir().dot(last.dir()) < 0.0 {
+                return HalfPlaneIntersection::Empty;
+            }
+            if c == 0.0 {
+                if h.dir().det(last.0 - h.0) < 0.0 {
+                    dq.pop_back();
+                } else {
+                    continue;
+                }
+            } else if c > 0.0 {
+                // turning by more than pi
+                return HalfPlaneIntersection::Empty;
+            }
+        }
+        dq.push_back(h);
+    }
+    while dq.len() > 2 && out(&dq[0], &dq[dq.len() - 2], &dq[dq.len() - 1]) {
+        dq.pop_back();
+    }
+    while dq.len() > 2 && out(&dq[dq.len() - 1], &dq[0], &dq[1]) {
+        dq.pop_front();
+    }
+    if dq.len() < 3 {
+        return HalfPlaneIntersection::Empty;
+    }
+    let m = dq.len();
+    HalfPlaneIntersection::Bounded(
+        (0..m)
+            .map(|i| dq[i].cross_point(&dq[(i + 1) % m]).unwrap())
+            .collect(),
+    )
+}
+
+#[snippet = "Circle"]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+    pub c: Vector2D,
+    pub r: f64,
+}
+
+#[snippet = "Circle"]
+#[allow(dead_code)]
+impl Circle {
+    pub fn new(c: Vector2D, r: f64) -> Circle {
+        Circle { c: c, r: r }
+    }
+    pub fn contains(&self, p: Vector2D) -> bool {
+        (p - self.c).len() <= self.r + EPS
+    }
+    #[doc = "intersections with the line through a and b"]
+    pub fn cross_line(&self, a: Vector2D, b: Vector2D) -> Vec<Vector2D> {
+        let d = b - a;
+        let h = a + d * ((self.c - a).dot(d) / d.dot(d));
+        let h2 = self.r * self.r - (self.c - h).dot(self.c - h);
+        if h2 < -EPS {
+            vec![]
+        } else if h2 < EPS {
+            vec![h]
+        } else {
+            let v = d.unit() * h2.sqrt();
+            vec![h - v, h + v]
+        }
+    }
+    pub fn cross_circle(&self, other: &Circle) -> Vec<Vector2D> {
+        let v = other.c - self.c;
+        let d = v.len();
+        if d < EPS || d > self.r + other.r + EPS || d < (self.r - other.r).abs() - EPS {
+            return vec![];
+        }
+        let a = (self.r * self.r - other.r * other.r + d * d) / (2.0 * d);
+        let p = self.c + v * (a / d);
+        let h2 = self.r * self.r - a * a;
+        if h2 < EPS {
+            vec![p]
+        } else {
+            let w = v.normal() * (h2.sqrt() / d);
+            vec![p + w, p - w]
+        }
+    }
+    // KACTL. tangent lines touching self at .0 and c2 (radius |r2|) at .1.
+    // negative r2 gives the internal ones.
+    fn tangents(&self, c2: Vector2D, r2: f64) -> Vec<(Vector2D, Vector2D)> {
+        let d = c2 - self.c;
+        let dr = self.r - r2;
+        let d2 = d.dot(d);
+        let h2 = d2 - dr * dr;
+        if d2 < EPS || h2 < -EPS {
+            return vec![];
+        }
+        let h = h2.max(0.0).sqrt();
+        let mut res = vec![];
+        for &sign in &[-1.0, 1.0] {
+            let v = (d * dr + d.normal() * (h * sign)) / d2;
+            res.push((self.c + v * self.r, c2 + v * r2));
+        }
+        if h2 < EPS {
+            res.pop();
+        }
+        res
+    }
+    #[doc = "points on the circle whose tangent passes through p"]
+    pub fn tangent_points(&self, p: Vector2D) -> Vec<Vector2D> {
+        self.tangents(p, 0.0).into_iter().map(|t| t.0).collect()
+    }
+    #[doc = "common tangent lines as (touching point on self, touching point on other). 0 to 4 lines"]
+    pub fn common_tangents(&self, other: &Circle) -> Vec<(Vector2D, Vector2D)> {
+        let mut res = self.tangents(other.c, other.r);
+        res.extend(self.tangents(other.c, -other.r));
+        res
+    }
+    pub fn intersection_area(&self, other: &Circle) -> f64 {
+        let d = (other.c - self.c).len();
+        let (r1, r2) = (self.r, other.r);
+        if d >= r1 + r2 {
+            return 0.0;
+        }
+        if d <= (r1 - r2).abs() {
+            let r = r1.min(r2);
+            return std::f64::consts::PI * r * r;
+        }
+        let clamp = |x: f64| x.max(-1.0).min(1.0);
+        let a1 = clamp((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).acos();
+        let a2 = clamp((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).acos();
+        let k = (-d + r1 + r2) * (d + r1 - r2) * (d - r1 + r2) * (d + r1 + r2);
+        r1 * r1 * a1 + r2 * r2 * a2 - 0.5 * k.max(0.0).sqrt()
+    }
+    #[doc = "None if a, b, c are collinear"]
+    pub fn circumcircle(a: Vector2D, b: Vector2D, c: Vector2D) -> Option<Circle> {
+        let b = b - a;
+        let c = c - a;
+        let d = 2.0 * b.det(c);
+        if d.abs() < EPS {
+            return None;
+        }
+        let (bb, cc) = (b.dot(b), c.dot(c));
+        let u = Vector2D((c.1 * bb - b.1 * cc) / d, (b.0 * cc - c.0 * bb) / d);
+        Some(Circle::new(a + u, u.len()))
+    }
+    pub fn incircle(a: Vector2D, b: Vector2D, c: Vector2D) -> Circle {
+        let la = (b - c).len();
+        let lb = (c - a).len();
+        let lc = (a - b).len();
+        let s = la + lb + lc;
+        let center = (a * la + b * lb + c * lc) / s;
+        Circle::new(center, (b - a).det(c - a).abs() / s)
+    }
+}
+
+use crate::xorshift::Xorshift;
+
+#[snippet = "smallest_enclosing_circle"]
+#[doc = "Welzl. ps must not be empty. expected O(n) over the shuffle by rng (seed it e.g. from the time against hacks)"]
+pub fn smallest_enclosing_circle(ps: &[Vector2D], rng: &mut Xorshift) -> Circle {
+    assert!(!ps.is_empty());
+    let mut ps = ps.to_vec();
+    for i in (1..ps.len()).rev() {
+        let j = rng.rand(i as u64 + 1) as usize;
+        ps.swap(i, j);
+    }
+    let n = ps.len();
+    let mut c = Circle::new(ps[0], 0.0);
+    for i in 1..n {
+        if c.contains(ps[i]) {
+            continue;
+        }
+        c = Circle::new(ps[i], 0.0);
+        for j in 0..i {
+            if c.contains(ps[j]) {
+                continue;
+            }
+            c = Circle::new((ps[i] + ps[j]) / 2.0, (ps[i] - ps[j]).len() / 2.0);
+            for k in 0..j {
+                if c.contains(ps[k]) {
+                    continue;
+                }
+                if let Some(cc) = Circle::circumcircle(ps[i], ps[j], ps[k]) {
+                    c = cc;
+                }
+            }
+        }
+    }
+    c
+}
+
+#[snippet = "Point"]
+pub trait Coord:
+    Copy
+    + PartialOrd
+    + Default
+    + std::ops::Add<Output = Self>
+    + std::ops::Sub<Output = Self>
+    + std::ops::Mul<Output = Self>
+    + std::ops::Neg<Output = Self>
+{
+    fn to_f64(self) -> f64;
+}
+#[snippet = "Point"]
+impl Coord for i64 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+#[snippet = "Point"]
+impl Coord for i128 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+#[snippet = "Point"]
+impl Coord for f64 {
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+#[snippet = "Point"]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+/// Exact point for integer coordinates. Use i128 when |x| > 1e9.
+pub struct Point<T>(pub T, pub T);
+
+#[snippet = "Point"]
+#[allow(dead_code)]
+impl<T: Coord> Point<T> {
+    pub fn dot(self, other: Point<T>) -> T {
+        self.0 * other.0 + self.1 * other.1
+    }
+    pub fn cross(self, other: Point<T>) -> T {
+        self.0 * other.1 - self.1 * other.0
+    }
+    pub fn norm2(self) -> T {
+        self.dot(self)
+    }
+    pub fn to_f64(self) -> Point<f64> {
+        Point(self.0.to_f64(), self.1.to_f64())
+    }
+}
+
+#[snippet = "Point"]
+impl<T: Coord> std::ops::Add for Point<T> {
+    type Output = Point<T>;
+    fn add(self, rhs: Point<T>) -> Point<T> {
+        Point(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+#[snippet = "Point"]
+impl<T: Coord> std::ops::Sub for Point<T> {
+    type Output = Point<T>;
+    fn sub(self, rhs: Point<T>) -> Point<T> {
+        Point(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+#[snippet = "Point"]
+impl<T: Coord> std::ops::Mul<T> for Point<T> {
+    type Output = Point<T>;
+    fn mul(self, rhs: T) -> Point<T> {
+        Point(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+#[snippet = "Point"]
+impl<T: Coord> std::ops::Neg for Point<T> {
+    type Output = Point<T>;
+    fn neg(self) -> Point<T> {
+        Point(-self.0, -self.1)
+    }
+}
+
+#[snippet = "Point"]
+impl From<Vector2D> for Point<f64> {
+    fn from(v: Vector2D) -> Point<f64> {
+        Point(v.0, v.1)
+    }
+}
+
+#[snippet = "Point"]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CCW {
+    CounterClockwise = 1,
+    Clockwise = -1,
+    /// c-a-b on a line
+    OnlineBack = 2,
+    /// a-b-c on a line
+    OnlineFront = -2,
+    /// a-c-b on a line
+    OnSegment = 0,
+}
+
+#[snippet = "Point"]
+#[doc = "where is c seen from a->b"]
+pub fn ccw<T: Coord>(a: Point<T>, b: Point<T>, c: Point<T>) -> CCW {
+    let zero = T::default();
+    let b = b - a;
+    let c = c - a;
+    let cr = b.cross(c);
+    if cr > zero {
+        CCW::CounterClockwise
+    } else if cr < zero {
+        CCW::Clockwise
+    } else if b.dot(c) < zero {
+        CCW::OnlineBack
+    } else if b.norm2() < c.norm2() {
+        CCW::OnlineFront
+    } else {
+        CCW::OnSegment
+    }
+}
+
+#[snippet = "Point"]
+#[doc = "do segment a-b and c-d share a point ? (touching counts)"]
+pub fn segments_intersect<T: Coord>(a: Point<T>, b: Point<T>, c: Point<T>, d: Point<T>) -> bool {
+    if a == b {
+        return ccw(c, d, a) == CCW::OnSegment;
+    }
+    if c == d {
+        return ccw(a, b, c) == CCW::OnSegment;
+    }
+    ccw(a, b, c) as i32 * ccw(a, b, d) as i32 <= 0 && ccw(c, d, a) as i32 * ccw(c, d, b) as i32 <= 0
+}
+
 use crate::total::Total;
 
 #[snippet = "convex_hull"]
 #[allow(dead_code)]
-fn convex_hull(vs: &[Vector2D]) -> Vec<usize> {
+#[doc = "indices of the convex hull in counter clockwise order. collinear points are removed. O(n log n)"]
+pub fn convex_hull<T: Coord>(vs: &[Point<T>]) -> Vec<usize> {
     let mut idx: Vec<usize> = (0..vs.len()).collect();
     idx.sort_by_key(|&i| Total((vs[i].0, vs[i].1)));
+    idx.dedup_by(|a, b| vs[*a] == vs[*b]);
+    if idx.len() <= 1 {
+        return idx;
+    }
 
-    let mut res = Vec::new();
+    let zero = T::default();
+    let mut res: Vec<usize> = Vec::new();
 
     for &i in &idx {
         while res.len() > 1
-            && Vector2D::det(
-                vs[res[res.len() - 1]] - vs[res[res.len() - 2]],
-                vs[i] - vs[res[res.len() - 1]],
-            ) <= 0.0
+            && (vs[res[res.len() - 1]] - vs[res[res.len() - 2]])
+                .cross(vs[i] - vs[res[res.len() - 1]])
+                <= zero
         {
             res.pop();
         }
@@ -98,8 +619,9 @@ fn convex_hull(vs: &[Vector2D]) -> Vec<usize> {
 
     for &i in idx.iter().rev().skip(1) {
         while res.len() > t
-            && (vs[res[res.len() - 1]] - vs[res[res.len() - 2]]).det(vs[i] - vs[res[res.len() - 1]])
-                <= 0.0
+            && (vs[res[res.len() - 1]] - vs[res[res.len() - 2]])
+                .cross(vs[i] - vs[res[res.len() - 1]])
+                <= zero
         {
             res.pop();
         }
@@ -110,6 +632,486 @@ fn convex_hull(vs: &[Vector2D]) -> Vec<usize> {
     res
 }
 
+#[snippet = "rotating_calipers"]
+#[doc = "farthest pair (dist^2, i, j) of vs, (0, 0, 0) if vs is empty. hull: output of convex_hull(vs). O(n)"]
+pub fn diameter<T: Coord>(vs: &[Point<T>], hull: &[usize]) -> (T, usize, usize) {
+    let n = hull.len();
+    if n == 0 {
+        return (T::default(), 0, 0);
+    }
+    let p = |i: usize| vs[hull[i % n]];
+    let mut best = (T::default(), hull[0], hull[0]);
+    let mut j = 1;
+    for i in 0..n {
+        let e = p(i + 1) - p(i);
+        // the vertex farthest from edge i
+        while e.cross(p(j + 1) - p(i)) > e.cross(p(j) - p(i)) {
+            j += 1;
+        }
+        for &k in &[i, i + 1] {
+            let d = (p(j) - p(k)).norm2();
+            if d > best.0 {
+                best = (d, hull[k % n], hull[j % n]);
+            }
+        }
+    }
+    best
+}
+
+#[snippet = "rotating_calipers"]
+#[doc = "min distance between two parallel lines enclosing vs. O(n)"]
+pub fn min_width<T: Coord>(vs: &[Point<T>], hull: &[usize]) -> f64 {
+    let n = hull.len();
+    if n < 3 {
+        return 0.0;
+    }
+    let p = |i: usize| vs[hull[i % n]];
+    let mut best = std::f64::MAX;
+    let mut j = 1;
+    for i in 0..n {
+        let e = p(i + 1) - p(i);
+        while e.cross(p(j + 1) - p(i)) > e.cross(p(j) - p(i)) {
+            j += 1;
+        }
+        best = best.min(e.cross(p(j) - p(i)).to_f64() / e.to_f64().norm2().sqrt());
+    }
+    best
+}
+
+#[snippet = "rotating_calipers"]
+// one side of the optimal rectangle lies on a hull edge
+fn min_rectangle<T: Coord, F: Fn(f64, f64) -> f64>(
+    vs: &[Point<T>],
+    hull: &[usize],
+    key: F,
+) -> (f64, [Point<f64>; 4]) {
+    let n = hull.len();
+    let p = |i: usize| vs[hull[i % n]];
+    if n == 1 {
+        let q = p(0).to_f64();
+        return (key(0.0, 0.0), [q; 4]);
+    }
+    let e0 = p(1) - p(0);
+    let mut k = (0..n)
+        .max_by_key(|&k| Total(e0.dot(p(k)).to_f64()))
+        .unwrap();
+    let mut l = (0..n)
+        .min_by_key(|&l| Total(e0.dot(p(l)).to_f64()))
+        .unwrap();
+    let mut j = 1;
+    let mut best = (std::f64::MAX, [Point(0.0, 0.0); 4]);
+    for i in 0..n {
+        let e = p(i + 1) - p(i);
+        while e.cross(p(j + 1) - p(i)) > e.cross(p(j) - p(i)) {
+            j += 1;
+        }
+        while e.dot(p(k + 1)) > e.dot(p(k)) {
+            k += 1;
+        }
+        while e.dot(p(l + 1)) < e.dot(p(l)) {
+            l += 1;
+        }
+        let len = e.to_f64().norm2().sqrt();
+        let u = e.to_f64() * (1.0 / len);
+        let nu = Point(-u.1, u.0);
+        let o = p(i).to_f64();
+        let h = e.cross(p(j) - p(i)).to_f64() / len;
+        let lo = u.dot(p(l).to_f64() - o);
+        let hi = u.dot(p(k).to_f64() - o);
+        let v = key(hi - lo, h);
+        if v < best.0 {
+            let a = o + u * lo;
+            let b = o + u * hi;
+            best = (v, [a, b, b + nu * h, a + nu * h]);
+        }
+    }
+    best
+}
+
+#[snippet = "rotating_calipers"]
+#[doc = "(area, corners in counter clockwise order). O(n)"]
+pub fn min_area_rectangle<T: Coord>(vs: &[Point<T>], hull: &[usize]) -> (f64, [Point<f64>; 4]) {
+    min_rectangle(vs, hull, |w, h| w * h)
+}
+
+#[snippet = "rotating_calipers"]
+#[doc = "(perimeter, corners in counter clockwise order). O(n)"]
+pub fn min_perimeter_rectangle<T: Coord>(
+    vs: &[Point<T>],
+    hull: &[usize],
+) -> (f64, [Point<f64>; 4]) {
+    min_rectangle(vs, hull, |w, h| 2.0 * (w + h))
+}
+
+#[snippet = "rotating_calipers"]
+#[doc = "max dist^2 between a point of ps and a point of qs. both convex in counter clockwise order. O(n+m)"]
+pub fn max_distance_convex<T: Coord>(ps: &[Point<T>], qs: &[Point<T>]) -> T {
+    // walk the vertices p - q of the Minkowski sum ps + (-qs)
+    let qs: Vec<Point<T>> = qs.iter().map(|&q| -q).collect();
+    let (n, m) = (ps.len(), qs.len());
+    let lowest = |xs: &[Point<T>]| {
+        (0..xs.len())
+            .min_by_key(|&i| Total((xs[i].1, xs[i].0)))
+            .unwrap()
+    };
+    let (i0, j0) = (lowest(ps), lowest(&qs));
+    let p = |i: usize| ps[(i0 + i) % n];
+    let q = |j: usize| qs[(j0 + j) % m];
+    let zero = T::default();
+    let mut best = (p(0) + q(0)).norm2();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        let c = (p(i + 1) - p(i)).cross(q(j + 1) - q(j));
+        if j == m || (i < n && c >= zero) {
+            i += 1;
+        }
+        if i == n + 1 || (j < m && c <= zero) {
+            j += 1;
+        }
+        let d = (p(i) + q(j)).norm2();
+        if d > best {
+            best = d;
+        }
+    }
+    best
+}
+
+#[snippet = "polygon"]
+#[doc = "twice the signed area. positive if counter clockwise"]
+pub fn polygon_area2<T: Coord>(ps: &[Point<T>]) -> T {
+    let n = ps.len();
+    let mut s = T::default();
+    for i in 0..n {
+        s = s + ps[i].cross(ps[(i + 1) % n]);
+    }
+    s
+}
+
+#[snippet = "polygon"]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Containment {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+#[snippet = "polygon"]
+#[doc = "simple polygon in either orientation. O(n)"]
+pub fn point_in_polygon<T: Coord>(ps: &[Point<T>], p: Point<T>) -> Containment {
+    let zero = T::default();
+    let n = ps.len();
+    let mut inside = false;
+    for i in 0..n {
+        let mut a = ps[i] - p;
+        let mut b = ps[(i + 1) % n] - p;
+        let cr = a.cross(b);
+        if cr == zero && a.dot(b) <= zero {
+            return Containment::Boundary;
+        }
+        if a.1 > b.1 {
+            std::mem::swap(&mut a, &mut b);
+        }
+        // does the edge cross the ray from p to +x ?
+        if a.1 <= zero && zero < b.1 && a.cross(b) > zero {
+            inside = !inside;
+        }
+    }
+    if inside {
+        Containment::Inside
+    } else {
+        Containment::Outside
+    }
+}
+
+#[snippet = "convex_contains"]
+#[doc = "ps: convex polygon in counter clockwise order without collinear vertices (e.g. convex_hull). O(log n)"]
+pub fn convex_contains<T: Coord>(ps: &[Point<T>], p: Point<T>) -> Containment {
+    let zero = T::default();
+    let n = ps.len();
+    if n < 3 {
+        return point_in_polygon(ps, p);
+    }
+    let p0 = ps[0];
+    let c1 = (ps[1] - p0).cross(p - p0);
+    let cn = (ps[n - 1] - p0).cross(p - p0);
+    if c1 < zero || cn > zero {
+        return Containment::Outside;
+    }
+    // the last i such that p is on the left of p0->ps[i]
+    let mut lo = 1;
+    let mut hi = n - 1;
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        if (ps[mid] - p0).cross(p - p0) >= zero {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    let c = (ps[lo + 1] - ps[lo]).cross(p - ps[lo]);
+    if c < zero {
+        Containment::Outside
+    } else if c == zero || (lo == 1 && c1 == zero) || (lo == n - 2 && cn == zero) {
+        Containment::Boundary
+    } else {
+        Containment::Inside
+    }
+}
+
+#[snippet = "convex_cut"]
+#[doc = "the part of convex polygon ps on the left of line a->b. O(n)"]
+pub fn convex_cut(ps: &[Point<f64>], a: Point<f64>, b: Point<f64>) -> Vec<Point<f64>> {
+    let n = ps.len();
+    let mut res = vec![];
+    for i in 0..n {
+        let p = ps[i];
+        let q = ps[(i + 1) % n];
+        let cp = (b - a).cross(p - a);
+        let cq = (b - a).cross(q - a);
+        if cp >= 0.0 {
+            res.push(p);
+        }
+        if cp * cq < 0.0 {
+            res.push(p + (q - p) * (cp / (cp - cq)));
+        }
+    }
+    res
+}
+
+#[snippet = "convex_cut"]
+#[doc = "intersection of two convex polygons in counter clockwise order. O(nm)"]
+pub fn convex_intersection(ps: &[Point<f64>], qs: &[Point<f64>]) -> Vec<Point<f64>> {
+    let mut res = ps.to_vec();
+    for i in 0..qs.len() {
+        res = convex_cut(&res, qs[i], qs[(i + 1) % qs.len()]);
+    }
+    res
+}
+
+#[snippet = "minkowski_sum"]
+#[doc = "ps + qs for convex polygons in counter clockwise order without collinear vertices (e.g. convex_hull). points and segments are fine. O(n + m)"]
+pub fn minkowski_sum<T: Coord>(ps: &[Point<T>], qs: &[Point<T>]) -> Vec<Point<T>> {
+    let (n, m) = (ps.len(), qs.len());
+    if n == 0 || m == 0 {
+        return vec![];
+    }
+    // start from the lowest vertices so that the edge directions increase in [0, 2pi)
+    let lowest = |xs: &[Point<T>]| {
+        let mut k = 0;
+        for i in 1..xs.len() {
+            if (xs[i].1, xs[i].0) < (xs[k].1, xs[k].0) {
+                k = i;
+            }
+        }
+        k
+    };
+    let (i0, j0) = (lowest(ps), lowest(qs));
+    let p = |i: usize| ps[(i0 + i) % n];
+    let q = |j: usize| qs[(j0 + j) % m];
+    // a single point has no edge
+    let (en, em) = (if n == 1 { 0 } else { n }, if m == 1 { 0 } else { m });
+    let mut res = vec![p(0) + q(0)];
+    let (mut i, mut j) = (0, 0);
+    while i < en || j < em {
+        let o = if i == en {
+            std::cmp::Ordering::Greater
+        } else if j == em {
+            std::cmp::Ordering::Less
+        } else {
+            arg_cmp(p(i + 1) - p(i), q(j + 1) - q(j))
+        };
+        if o != std::cmp::Ordering::Greater {
+            i += 1;
+        }
+        if o != std::cmp::Ordering::Less {
+            j += 1;
+        }
+        if i < en || j < em {
+            res.push(p(i) + q(j));
+        }
+    }
+    res
+}
+
+#[snippet = "minkowski_sum"]
+#[allow(dead_code)]
+pub fn convex_polygons_intersect<T: Coord>(ps: &[Point<T>], qs: &[Point<T>]) -> bool {
+    let qs: Vec<Point<T>> = qs.iter().map(|&q| -q).collect();
+    let s = minkowski_sum(ps, &qs);
+    convex_contains(&s, Point(T::default(), T::default())) != Containment::Outside
+}
+
+#[snippet = "minkowski_sum"]
+#[allow(dead_code)]
+#[doc = "0 if they intersect. O(n + m)"]
+pub fn convex_polygons_distance<T: Coord>(ps: &[Point<T>], qs: &[Point<T>]) -> f64 {
+    let qs: Vec<Point<T>> = qs.iter().map(|&q| -q).collect();
+    let s = minkowski_sum(ps, &qs);
+    if convex_contains(&s, Point(T::default(), T::default())) != Containment::Outside {
+        return 0.0;
+    }
+    let mut res = std::f64::MAX;
+    for i in 0..s.len() {
+        let a = s[i].to_f64();
+        let d = s[(i + 1) % s.len()].to_f64() - a;
+        let t = if d.norm2() == 0.0 {
+            0.0
+        } else {
+            (-a.dot(d) / d.norm2()).max(0.0).min(1.0)
+        };
+        res = res.min((a + d * t).norm2().sqrt());
+    }
+    res
+}
+
+#[snippet = "minkowski_sum"]
+#[allow(dead_code)]
+#[doc = "first time t >= 0 at which ps moving with velocity v touches qs. None if never. O(n + m)"]
+pub fn collision_time<T: Coord>(ps: &[Point<T>], v: Point<T>, qs: &[Point<T>]) -> Option<f64> {
+    let zero = T::default();
+    // t v must enter qs - ps
+    let ps: Vec<Point<T>> = ps.iter().map(|&p| -p).collect();
+    let s = minkowski_sum(qs, &ps);
+    if convex_contains(&s, Point(zero, zero)) != Containment::Outside {
+        return Some(0.0);
+    }
+    let mut res: Option<f64> = None;
+    let mut update = |t: f64| {
+        if res.map_or(true, |r| t < r) {
+            res = Some(t);
+        }
+    };
+    for i in 0..s.len() {
+        let a = s[i];
+        let d = s[(i + 1) % s.len()] - a;
+        let den = v.cross(d);
+        if den == zero {
+            // parallel. only the endpoints can be the first contact
+            for &e in &[a, a + d] {
+                if v.cross(e) == zero && v.dot(e) >= zero && v != Point(zero, zero) {
+                    update(v.dot(e).to_f64() / v.norm2().to_f64());
+                }
+            }
+            continue;
+        }
+        // t v = a + u d
+        let (t, u) = (a.cross(d), a.cross(v));
+        let (t, u, den) = if den < zero {
+            (-t, -u, -den)
+        } else {
+            (t, u, den)
+        };
+        if t >= zero && u >= zero && u <= den {
+            update(t.to_f64() / den.to_f64());
+        }
+    }
+    res
+}
+
+use crate::number::gcd;
+
+#[snippet = "pick"]
+#[doc = "lattice points (strictly inside, on the boundary) of a simple lattice polygon. Pick's theorem"]
+pub fn lattice_points(ps: &[Point<i64>]) -> (i64, i64) {
+    let n = ps.len();
+    let mut b = 0;
+    for i in 0..n {
+        let d = ps[(i + 1) % n] - ps[i];
+        b += gcd(d.0.abs(), d.1.abs());
+    }
+    let a2 = polygon_area2(ps).abs();
+    ((a2 - b + 2) / 2, b)
+}
+
+#[snippet = "arg_sort"]
+#[doc = "compare by argument in [0, 2pi) without atan2. the zero vector comes first"]
+pub fn arg_cmp<T: Coord>(a: Point<T>, b: Point<T>) -> std::cmp::Ordering {
+    let zero = T::default();
+    // 0: zero, 1: [0, pi), 2: [pi, 2pi)
+    let half = |p: Point<T>| {
+        if p.0 == zero && p.1 == zero {
+            0
+        } else if p.1 < zero || p.1 == zero && p.0 < zero {
+            2
+        } else {
+            1
+        }
+    };
+    half(a)
+        .cmp(&half(b))
+        .then_with(|| zero.partial_cmp(&a.cross(b)).unwrap())
+}
+
+#[snippet = "arg_sort"]
+#[allow(dead_code)]
+pub fn arg_sort<T: Coord>(vs: &mut [Point<T>]) {
+    vs.sort_by(|&a, &b| arg_cmp(a, b));
+}
+
+#[snippet = "arg_sort"]
+#[allow(dead_code)]
+#[doc = "indices of points other than c in counter clockwise order around c, starting from the +x direction. ties by distance"]
+pub fn radial_order<T: Coord>(c: Point<T>, ps: &[Point<T>]) -> Vec<usize> {
+    let mut res: Vec<usize> = (0..ps.len()).filter(|&i| ps[i] != c).collect();
+    res.sort_by(|&i, &j| {
+        let (a, b) = (ps[i] - c, ps[j] - c);
+        arg_cmp(a, b).then_with(|| a.norm2().partial_cmp(&b.norm2()).unwrap())
+    });
+    res
+}
+
+#[snippet = "arg_sort"]
+/// Directions of points seen from a center, for counting points in angular windows
+pub struct AngularCounter<T> {
+    vs: Vec<Point<T>>,
+}
+
+#[snippet = "arg_sort"]
+#[allow(dead_code)]
+impl<T: Coord> AngularCounter<T> {
+    #[doc = "points equal to c are ignored"]
+    pub fn new(c: Point<T>, ps: &[Point<T>]) -> AngularCounter<T> {
+        let mut vs: Vec<Point<T>> = ps.iter().filter(|&&p| p != c).map(|&p| p - c).collect();
+        arg_sort(&mut vs);
+        AngularCounter { vs: vs }
+    }
+    pub fn len(&self) -> usize {
+        self.vs.len()
+    }
+    // number of directions with arg < arg(d) (or <= if inclusive)
+    fn rank(&self, d: Point<T>, inclusive: bool) -> usize {
+        let (mut lo, mut hi) = (0, self.vs.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let o = arg_cmp(self.vs[mid], d);
+            if o == std::cmp::Ordering::Less || inclusive && o == std::cmp::Ordering::Equal {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+    #[doc = "points in the closed window turning counter clockwise from direction a to direction b. O(log n)"]
+    pub fn count(&self, a: Point<T>, b: Point<T>) -> usize {
+        let (l, r) = (self.rank(a, false), self.rank(b, true));
+        if arg_cmp(a, b) != std::cmp::Ordering::Greater {
+            r - l
+        } else {
+            self.vs.len() - l + r
+        }
+    }
+    #[doc = "points strictly on the left of direction d != 0. O(log n)"]
+    pub fn count_left(&self, d: Point<T>) -> usize {
+        let (l, r) = (self.rank(d, true), self.rank(-d, false));
+        if arg_cmp(d, -d) == std::cmp::Ordering::Less {
+            r - l
+        } else {
+            self.vs.len() - l + r
+        }
+    }
+}
+
 #[snippet = "closest_pair"]
 pub fn closest_pair(ps: &[(f64, f64)]) -> ((f64, f64), (f64, f64)) {
     fn d(p1: (f64, f64), p2: (f64, f64)) -> f64 {
@@ -204,17 +1206,677 @@ pub fn is_intersected(a: Vector2D, b: Vector2D, c: Vector2D, d: Vector2D) -> boo
 
 #[test]
 fn test_convex_hull() {
-    let vs = vec![
+    let vs: Vec<Point<f64>> = vec![
         Vector2D(-1.0, -1.0),
         Vector2D(-1.0, 1.0),
         Vector2D(1.0, 1.0),
         Vector2D(1.0, -1.0),
         Vector2D(0.0, 0.0),
         Vector2D(0.1, 0.1),
-    ];
+    ]
+    .into_iter()
+    .map(|v| v.into())
+    .collect();
 
     let mut idx = convex_hull(&vs);
     idx.sort();
 
     assert_eq!(&idx, &[0, 1, 2, 3]);
 }
+
+#[test]
+fn test_convex_hull_large_coord() {
+    let m = 1_000_000_000_000_000_000i128;
+    let vs = vec![
+        Point(0, 0),
+        Point(2 * m, 1),
+        Point(m, 0),
+        Point(m, m),
+        Point(m + 1, 1),
+        Point(2 * m + 2, 2 * m + 1),
+        Point(m + 1, m + 1),
+    ];
+    // (m, m) lies on the edge between (m+1, m+1) and (0, 0)
+    assert_eq!(convex_hull(&vs), vec![0, 2, 1, 5, 6]);
+    let vs = vec![Point(0i64, 0), Point(1, 1), Point(2, 2)];
+    assert_eq!(convex_hull(&vs), vec![0, 2]);
+    assert_eq!(convex_hull(&[Point(3i64, 3)]), vec![0]);
+    assert_eq!(convex_hull(&[Point(3i64, 3); 3]), vec![0]);
+}
+
+#[test]
+fn test_ccw() {
+    let a = Point(0i64, 0);
+    let b = Point(2i64, 0);
+    assert_eq!(ccw(a, b, Point(1, 1)), CCW::CounterClockwise);
+    assert_eq!(ccw(a, b, Point(1, -1)), CCW::Clockwise);
+    assert_eq!(ccw(a, b, Point(-1, 0)), CCW::OnlineBack);
+    assert_eq!(ccw(a, b, Point(3, 0)), CCW::OnlineFront);
+    assert_eq!(ccw(a, b, Point(1, 0)), CCW::OnSegment);
+    assert_eq!(ccw(a, b, Point(2, 0)), CCW::OnSegment);
+}
+
+#[test]
+fn test_segments_intersect() {
+    let p = |x: i64, y: i64| Point(x, y);
+    assert!(segments_intersect(p(0, 0), p(2, 2), p(0, 2), p(2, 0)));
+    assert!(segments_intersect(p(0, 0), p(2, 2), p(2, 2), p(3, 0)));
+    assert!(segments_intersect(p(0, 0), p(2, 0), p(1, 0), p(3, 0)));
+    assert!(!segments_intersect(p(0, 0), p(1, 0), p(2, 0), p(3, 0)));
+    assert!(!segments_intersect(p(0, 0), p(2, 2), p(1, 0), p(3, 2)));
+    // degenerate segments are points
+    assert!(segments_intersect(p(0, 0), p(0, 4), p(0, 1), p(0, 1)));
+    assert!(!segments_intersect(p(0, 5), p(0, 5), p(0, 0), p(0, 4)));
+    assert!(segments_intersect(p(1, 1), p(1, 1), p(1, 1), p(1, 1)));
+    assert!(!segments_intersect(p(1, 1), p(1, 1), p(1, 2), p(1, 2)));
+    let m = 1_000_000_000i64;
+    // cross products here are around 4e18, fine in i64
+    assert!(!segments_intersect(
+        p(-m, -m),
+        p(m, m - 1),
+        p(-m, -m + 1),
+        p(m, m)
+    ));
+}
+
+#[test]
+fn test_polygon_area() {
+    let ps = vec![Point(0i64, 0), Point(4, 0), Point(4, 3), Point(0, 3)];
+    assert_eq!(polygon_area2(&ps), 24);
+    let rev: Vec<_> = ps.iter().rev().cloned().collect();
+    assert_eq!(polygon_area2(&rev), -24);
+}
+
+#[test]
+fn test_point_in_polygon() {
+    // concave
+    let ps = vec![
+        Point(0i64, 0),
+        Point(4, 0),
+        Point(4, 4),
+        Point(2, 1),
+        Point(0, 4),
+    ];
+    assert_eq!(point_in_polygon(&ps, Point(1, 1)), Containment::Inside);
+    assert_eq!(point_in_polygon(&ps, Point(2, 2)), Containment::Outside);
+    assert_eq!(point_in_polygon(&ps, Point(2, 1)), Containment::Boundary);
+    assert_eq!(point_in_polygon(&ps, Point(3, 0)), Containment::Boundary);
+    assert_eq!(point_in_polygon(&ps, Point(5, 0)), Containment::Outside);
+    assert_eq!(point_in_polygon(&ps, Point(-1, 0)), Containment::Outside);
+    assert_eq!(point_in_polygon(&ps, Point(3, 2)), Containment::Inside);
+}
+
+#[test]
+fn test_convex_contains() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    let vs: Vec<Point<i64>> = (0..30)
+        .map(|_| Point(rng.rand(21) as i64 - 10, rng.rand(21) as i64 - 10))
+        .collect();
+    let hull: Vec<Point<i64>> = convex_hull(&vs).into_iter().map(|i| vs[i]).collect();
+    for x in -12..13 {
+        for y in -12..13 {
+            let p = Point(x, y);
+            assert_eq!(convex_contains(&hull, p), point_in_polygon(&hull, p));
+        }
+    }
+    let tri = vec![Point(0i64, 0), Point(2, 0), Point(0, 2)];
+    assert_eq!(convex_contains(&tri, Point(0, 0)), Containment::Boundary);
+    assert_eq!(convex_contains(&tri, Point(0, 1)), Containment::Boundary);
+    assert_eq!(convex_contains(&tri, Point(1, 1)), Containment::Boundary);
+    assert_eq!(convex_contains(&tri, Point(3, 0)), Containment::Outside);
+    assert_eq!(convex_contains(&tri, Point(0, 3)), Containment::Outside);
+}
+
+#[test]
+fn test_convex_cut() {
+    let sq = vec![
+        Point(0.0, 0.0),
+        Point(2.0, 0.0),
+        Point(2.0, 2.0),
+        Point(0.0, 2.0),
+    ];
+    let left = convex_cut(&sq, Point(1.0, 0.0), Point(1.0, 1.0));
+    assert_eq!(polygon_area2(&left), 4.0);
+    let tri = convex_cut(&sq, Point(0.0, 0.0), Point(2.0, 2.0));
+    assert_eq!(polygon_area2(&tri), 4.0);
+    assert!(convex_cut(&sq, Point(3.0, 0.0), Point(3.0, -1.0)).is_empty());
+
+    let diamond = vec![
+        Point(1.0, -0.5),
+        Point(2.5, 1.0),
+        Point(1.0, 2.5),
+        Point(-0.5, 1.0),
+    ];
+    let inter = convex_intersection(&sq, &diamond);
+    assert!((polygon_area2(&inter) - 7.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_minkowski_sum() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    let hull = |ps: Vec<Point<i64>>| -> Vec<Point<i64>> {
+        convex_hull(&ps).into_iter().map(|i| ps[i]).collect()
+    };
+    let sq = vec![Point(0i64, 0), Point(1, 0), Point(1, 1), Point(0, 1)];
+    let tri = vec![Point(0i64, 0), Point(2, 0), Point(0, 2)];
+    assert_eq!(
+        minkowski_sum(&sq, &tri),
+        vec![
+            Point(0, 0),
+            Point(3, 0),
+            Point(3, 1),
+            Point(1, 3),
+            Point(0, 3)
+        ]
+    );
+    for _ in 0..500 {
+        let gen = |rng: &mut Xorshift| {
+            let n = rng.rand(6) as usize + 1;
+            let (ox, oy) = (rng.rand(21) as i64 - 10, rng.rand(21) as i64 - 10);
+            hull(
+                (0..n)
+                    .map(|_| Point(ox + rng.rand(5) as i64, oy + rng.rand(5) as i64))
+                    .collect(),
+            )
+        };
+        let ps = gen(&mut rng);
+        let qs = gen(&mut rng);
+        let mut sums = vec![];
+        for &p in &ps {
+            for &q in &qs {
+                sums.push(p + q);
+            }
+        }
+        let mut expected = hull(sums);
+        let mut got = minkowski_sum(&ps, &qs);
+        expected.sort_by_key(|p| (p.0, p.1));
+        got.sort_by_key(|p| (p.0, p.1));
+        assert_eq!(got, expected);
+
+        let mut crossing = ps
+            .iter()
+            .any(|&p| point_in_polygon(&qs, p) != Containment::Outside)
+            || qs
+                .iter()
+                .any(|&q| point_in_polygon(&ps, q) != Containment::Outside);
+        let mut dist = std::f64::MAX;
+        let seg = |a: Point<i64>, b: Point<i64>| {
+            Segment(
+                Vector2D(a.0 as f64, a.1 as f64),
+                Vector2D(b.0 as f64, b.1 as f64),
+            )
+        };
+        for i in 0..ps.len() {
+            for j in 0..qs.len() {
+                let (a, b) = (ps[i], ps[(i + 1) % ps.len()]);
+                let (c, d) = (qs[j], qs[(j + 1) % qs.len()]);
+                crossing |= segments_intersect(a, b, c, d);
+                dist = dist.min(seg(a, b).distance(&seg(c, d)));
+            }
+        }
+        assert_eq!(convex_polygons_intersect(&ps, &qs), crossing);
+        let expected = if crossing { 0.0 } else { dist };
+        assert!((convex_polygons_distance(&ps, &qs) - expected).abs() < 1e-9);
+
+        // translate ps along v and check the first contact by sampling
+        let v = Point(rng.rand(11) as i64 - 5, rng.rand(11) as i64 - 5);
+        let to_f = |xs: &[Point<i64>], t: f64| -> Vec<Point<f64>> {
+            xs.iter().map(|&p| p.to_f64() + v.to_f64() * t).collect()
+        };
+        let qf = to_f(&qs, 0.0);
+        let d_at = |t: f64| convex_polygons_distance(&to_f(&ps, t), &qf);
+        match collision_time(&ps, v, &qs) {
+            Some(t) => {
+                assert!(d_at(t) < 1e-6);
+                assert_eq!(t == 0.0, convex_polygons_intersect(&ps, &qs));
+                for k in 0..20 {
+                    assert!(t == 0.0 || d_at(t * k as f64 / 20.0) > 1e-9);
+                }
+            }
+            None => {
+                for k in 0..50 {
+                    assert!(d_at(k as f64) > 1e-9);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_lattice_points() {
+    let ps = vec![Point(0i64, 0), Point(4, 0), Point(0, 4)];
+    assert_eq!(lattice_points(&ps), (3, 12));
+    let ps = vec![Point(0i64, 0), Point(3, 1), Point(1, 3)];
+    assert_eq!(lattice_points(&ps), (3, 4));
+}
+
+#[test]
+fn test_arg_sort() {
+    let mut vs = vec![];
+    for x in -3..=3i64 {
+        for y in -3..=3 {
+            vs.push(Point(x, y));
+        }
+    }
+    arg_sort(&mut vs);
+    assert_eq!(vs[0], Point(0, 0));
+    assert_eq!(vs[1], Point(1, 0));
+    let ang = |p: Point<i64>| {
+        let a = (p.1 as f64).atan2(p.0 as f64);
+        if a < 0.0 {
+            a + 2.0 * std::f64::consts::PI
+        } else {
+            a
+        }
+    };
+    for i in 2..vs.len() {
+        let (a, b) = (ang(vs[i - 1]), ang(vs[i]));
+        assert!(a <= b + 1e-12);
+        assert_eq!(
+            arg_cmp(vs[i - 1], vs[i]) == std::cmp::Ordering::Equal,
+            (a - b).abs() < 1e-12
+        );
+    }
+    let m = 1_000_000_000i64;
+    assert_eq!(
+        arg_cmp(Point(m, 1), Point(m - 1, 1)),
+        std::cmp::Ordering::Less
+    );
+    assert_eq!(
+        arg_cmp(Point(-m, -1), Point(-m, 0)),
+        std::cmp::Ordering::Greater
+    );
+}
+
+#[test]
+fn test_angular_counter() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    for _ in 0..100 {
+        let n = rng.rand(30) as usize;
+        let p = |rng: &mut Xorshift| Point(rng.rand(7) as i64 - 3, rng.rand(7) as i64 - 3);
+        let c = p(&mut rng);
+        let ps: Vec<Point<i64>> = (0..n).map(|_| p(&mut rng)).collect();
+        let order = radial_order(c, &ps);
+        assert_eq!(order.len(), ps.iter().filter(|&&q| q != c).count());
+        for w in order.windows(2) {
+            let (a, b) = (ps[w[0]] - c, ps[w[1]] - c);
+            assert!(arg_cmp(a, b) != std::cmp::Ordering::Greater);
+        }
+        let ac = AngularCounter::new(c, &ps);
+        for _ in 0..20 {
+            let (mut a, mut b) = (p(&mut rng), p(&mut rng));
+            while a == Point(0, 0) {
+                a = p(&mut rng);
+            }
+            while b == Point(0, 0) {
+                b = p(&mut rng);
+            }
+            let left = order.iter().filter(|&&i| a.cross(ps[i] - c) > 0).count();
+            assert_eq!(ac.count_left(a), left);
+            // [a, b] counter clockwise
+            let inside = |v: Point<i64>| {
+                if a.cross(b) == 0 && a.dot(b) > 0 {
+                    return v.cross(a) == 0 && v.dot(a) > 0;
+                }
+                let ge_a = a.cross(v) > 0 || a.cross(v) == 0 && a.dot(v) > 0;
+                let le_b = v.cross(b) > 0 || v.cross(b) == 0 && v.dot(b) > 0;
+                if a.cross(b) > 0 {
+                    ge_a && le_b
+                } else {
+                    ge_a || le_b
+                }
+            };
+            let cnt = order.iter().filter(|&&i| inside(ps[i] - c)).count();
+            assert_eq!(ac.count(a, b), cnt);
+        }
+    }
+}
+
+#[test]
+fn test_circle_cross() {
+    let c = Circle::new(Vector2D(0.0, 0.0), 1.0);
+    let ps = c.cross_line(Vector2D(-2.0, 0.0), Vector2D(2.0, 0.0));
+    assert_eq!(ps, vec![Vector2D(-1.0, 0.0), Vector2D(1.0, 0.0)]);
+    assert_eq!(
+        c.cross_line(Vector2D(-2.0, 1.0), Vector2D(2.0, 1.0)),
+        vec![Vector2D(0.0, 1.0)]
+    );
+    assert!(c
+        .cross_line(Vector2D(-2.0, 2.0), Vector2D(2.0, 2.0))
+        .is_empty());
+
+    let d = Circle::new(Vector2D(1.0, 0.0), 1.0);
+    let ps = c.cross_circle(&d);
+    assert_eq!(ps.len(), 2);
+    for p in ps {
+        assert!((p.0 - 0.5).abs() < 1e-9);
+        assert!(((p - c.c).len() - 1.0).abs() < 1e-9);
+    }
+    assert_eq!(
+        c.cross_circle(&Circle::new(Vector2D(2.0, 0.0), 1.0)),
+        vec![Vector2D(1.0, 0.0)]
+    );
+    assert_eq!(
+        c.cross_circle(&Circle::new(Vector2D(0.5, 0.0), 0.5)),
+        vec![Vector2D(1.0, 0.0)]
+    );
+    assert!(c
+        .cross_circle(&Circle::new(Vector2D(3.0, 0.0), 1.0))
+        .is_empty());
+    assert!(c
+        .cross_circle(&Circle::new(Vector2D(0.1, 0.0), 0.5))
+        .is_empty());
+}
+
+#[test]
+fn test_circle_tangents() {
+    let c = Circle::new(Vector2D(0.0, 0.0), 1.0);
+    let ps = c.tangent_points(Vector2D(2.0, 0.0));
+    assert_eq!(ps.len(), 2);
+    for p in ps {
+        // radius is perpendicular to the tangent
+        assert!(p.dot(p - Vector2D(2.0, 0.0)).abs() < 1e-9);
+    }
+    let count = |x: f64, r: f64| c.common_tangents(&Circle::new(Vector2D(x, 0.0), r)).len();
+    assert_eq!(count(4.0, 1.0), 4);
+    assert_eq!(count(2.0, 1.0), 3);
+    assert_eq!(count(1.0, 1.0), 2);
+    assert_eq!(count(0.5, 0.5), 1);
+    assert_eq!(count(0.2, 0.5), 0);
+    let d = Circle::new(Vector2D(5.0, 1.0), 2.0);
+    for (p, q) in c.common_tangents(&d) {
+        assert!(((p - c.c).len() - c.r).abs() < 1e-9);
+        assert!(((q - d.c).len() - d.r).abs() < 1e-9);
+        assert!((p - c.c).dot(q - p).abs() < 1e-9);
+        assert!((q - d.c).dot(q - p).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_circle_intersection_area() {
+    let pi = std::f64::consts::PI;
+    let c = Circle::new(Vector2D(0.0, 0.0), 1.0);
+    assert_eq!(
+        c.intersection_area(&Circle::new(Vector2D(3.0, 0.0), 1.0)),
+        0.0
+    );
+    assert!((c.intersection_area(&Circle::new(Vector2D(0.1, 0.0), 2.0)) - pi).abs() < 1e-9);
+    // lens of two unit circles at distance 1
+    let lens = 2.0 * pi / 3.0 - 3.0f64.sqrt() / 2.0;
+    assert!((c.intersection_area(&Circle::new(Vector2D(1.0, 0.0), 1.0)) - lens).abs() < 1e-9);
+}
+
+#[test]
+fn test_circumcircle_incircle() {
+    let (a, b, c) = (Vector2D(0.0, 0.0), Vector2D(4.0, 0.0), Vector2D(0.0, 3.0));
+    let cc = Circle::circumcircle(a, b, c).unwrap();
+    assert!((cc.c - Vector2D(2.0, 1.5)).len() < 1e-9);
+    assert!((cc.r - 2.5).abs() < 1e-9);
+    let ic = Circle::incircle(a, b, c);
+    assert!((ic.c - Vector2D(1.0, 1.0)).len() < 1e-9);
+    assert!((ic.r - 1.0).abs() < 1e-9);
+    assert!(Circle::circumcircle(a, b, Vector2D(8.0, 0.0)).is_none());
+}
+
+#[test]
+fn test_smallest_enclosing_circle() {
+    let mut rng = Xorshift::with_seed(1);
+    for _ in 0..50 {
+        let n = rng.rand(8) as usize + 1;
+        let ps: Vec<Vector2D> = (0..n)
+            .map(|_| Vector2D(rng.randf() * 10.0, rng.randf() * 10.0))
+            .collect();
+        let c = smallest_enclosing_circle(&ps, &mut rng);
+        let mut best = std::f64::MAX;
+        for i in 0..n {
+            for j in i..n {
+                let mut cands = vec![Circle::new(
+                    (ps[i] + ps[j]) / 2.0,
+                    (ps[i] - ps[j]).len() / 2.0,
+                )];
+                for k in j + 1..n {
+                    cands.extend(Circle::circumcircle(ps[i], ps[j], ps[k]));
+                }
+                for d in cands {
+                    if ps.iter().all(|&p| (p - d.c).len() <= d.r + 1e-9) {
+                        best = best.min(d.r);
+                    }
+                }
+            }
+        }
+        assert!(ps.iter().all(|&p| (p - c.c).len() <= c.r + 1e-9));
+        assert!((c.r - best).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_line_projection_reflection() {
+    let l = Line(Vector2D(0.0, 0.0), Vector2D(3.0, 4.0));
+    let p = l.projection(Vector2D(2.0, 5.0));
+    assert!((p - Vector2D(3.12, 4.16)).len() < 1e-9);
+    let r = l.reflection(Vector2D(2.0, 5.0));
+    assert!((r - Vector2D(4.24, 3.32)).len() < 1e-9);
+    assert!((l.distance(Vector2D(2.0, 5.0)) - 1.4).abs() < 1e-9);
+
+    let m = Line(Vector2D(0.0, 1.0), Vector2D(1.0, 1.0));
+    let x = Line(Vector2D(2.0, 0.0), Vector2D(2.0, 3.0));
+    assert!(m.is_parallel(&Line(Vector2D(5.0, 3.0), Vector2D(-1.0, 3.0))));
+    assert!(m.is_orthogonal(&x));
+    assert_eq!(m.cross_point(&x), Some(Vector2D(2.0, 1.0)));
+    assert_eq!(m.cross_point(&m), None);
+    assert!((m.angle(&x) - std::f64::consts::PI / 2.0).abs() < 1e-9);
+    assert!(
+        (m.angle(&Line(Vector2D(0.0, 0.0), Vector2D(-1.0, -1.0))) - std::f64::consts::PI / 4.0)
+            .abs()
+            < 1e-9
+    );
+}
+
+#[test]
+fn test_segment() {
+    let s = |a: f64, b: f64, c: f64, d: f64| Segment(Vector2D(a, b), Vector2D(c, d));
+    assert!(s(0.0, 0.0, 3.0, 0.0).intersects(&s(1.0, 1.0, 2.0, -1.0)));
+    assert!(s(0.0, 0.0, 3.0, 0.0).intersects(&s(3.0, 0.0, 4.0, 1.0)));
+    assert!(s(0.0, 0.0, 3.0, 0.0).intersects(&s(2.0, 0.0, 5.0, 0.0)));
+    assert!(!s(0.0, 0.0, 3.0, 0.0).intersects(&s(4.0, 0.0, 5.0, 0.0)));
+    assert!(!s(0.0, 0.0, 3.0, 0.0).intersects(&s(3.0, 1.0, 3.0, 2.0)));
+    // zero-length segments are points
+    assert!(s(0.0, 0.0, 3.0, 0.0).intersects(&s(1.0, 0.0, 1.0, 0.0)));
+    assert!(!s(0.0, 0.0, 3.0, 0.0).intersects(&s(4.0, 0.0, 4.0, 0.0)));
+    assert!(!s(4.0, 0.0, 4.0, 0.0).intersects(&s(0.0, 0.0, 3.0, 0.0)));
+
+    assert_eq!(
+        s(0.0, 0.0, 2.0, 0.0).cross_point(&s(1.0, 1.0, 1.0, -1.0)),
+        Some(Vector2D(1.0, 0.0))
+    );
+    assert_eq!(
+        s(0.0, 0.0, 2.0, 0.0).cross_point(&s(1.0, 0.0, 5.0, 0.0)),
+        Some(Vector2D(1.0, 0.0))
+    );
+    assert_eq!(
+        s(0.0, 0.0, 2.0, 0.0).cross_point(&s(3.0, 1.0, 3.0, -1.0)),
+        None
+    );
+
+    assert!((s(0.0, 0.0, 1.0, 0.0).distance(&s(0.0, 1.0, 1.0, 1.0)) - 1.0).abs() < 1e-9);
+    assert!((s(0.0, 0.0, 1.0, 0.0).distance(&s(2.0, 1.0, 1.0, 2.0)) - 2.0f64.sqrt()).abs() < 1e-9);
+    assert!((s(-1.0, 0.0, 1.0, 0.0).distance(&s(0.0, 1.0, 0.0, 2.0)) - 1.0).abs() < 1e-9);
+    assert_eq!(s(0.0, 0.0, 2.0, 2.0).distance(&s(0.0, 2.0, 2.0, 0.0)), 0.0);
+    assert_eq!(
+        s(1.0, 1.0, 1.0, 1.0).distance_point(Vector2D(4.0, 5.0)),
+        5.0
+    );
+}
+
+#[test]
+fn test_rotating_calipers() {
+    use crate::xorshift::Xorshift;
+    assert_eq!(diameter::<i64>(&[], &convex_hull::<i64>(&[])), (0, 0, 0));
+    let mut rng = Xorshift::with_seed(3);
+    for _ in 0..100 {
+        let n = rng.rand(20) as usize + 1;
+        let vs: Vec<Point<i64>> = (0..n)
+            .map(|_| Point(rng.rand(41) as i64 - 20, rng.rand(41) as i64 - 20))
+            .collect();
+        let hull = convex_hull(&vs);
+
+        let (d, i, j) = diameter(&vs, &hull);
+        assert_eq!((vs[i] - vs[j]).norm2(), d);
+        let brute = (0..n)
+            .flat_map(|i| (0..n).map(move |j| (i, j)))
+            .map(|(i, j)| (vs[i] - vs[j]).norm2())
+            .max()
+            .unwrap();
+        assert_eq!(d, brute);
+
+        if hull.len() < 3 {
+            continue;
+        }
+        // brute force: one side lies on a hull edge
+        let mut width = std::f64::MAX;
+        let mut area = std::f64::MAX;
+        let mut peri = std::f64::MAX;
+        for a in 0..hull.len() {
+            let o = vs[hull[a]].to_f64();
+            let e = vs[hull[(a + 1) % hull.len()]].to_f64() - o;
+            let len = e.norm2().sqrt();
+            let h = vs
+                .iter()
+                .map(|v| e.cross(v.to_f64() - o) / len)
+                .fold(0.0, f64::max);
+            let hi = vs
+                .iter()
+                .map(|v| e.dot(v.to_f64() - o) / len)
+                .fold(std::f64::MIN, f64::max);
+            let lo = vs
+                .iter()
+                .map(|v| e.dot(v.to_f64() - o) / len)
+                .fold(std::f64::MAX, f64::min);
+            width = width.min(h);
+            area = area.min(h * (hi - lo));
+            peri = peri.min(2.0 * (h + hi - lo));
+        }
+        assert!((min_width(&vs, &hull) - width).abs() < 1e-9);
+        let (a, rect) = min_area_rectangle(&vs, &hull);
+        assert!((a - area).abs() < 1e-9);
+        assert!((polygon_area2(&rect) / 2.0 - area).abs() < 1e-6);
+        for v in &vs {
+            for k in 0..4 {
+                let (s, t) = (rect[k], rect[(k + 1) % 4]);
+                assert!((t - s).cross(v.to_f64() - s) > -1e-6);
+            }
+        }
+        assert!((min_perimeter_rectangle(&vs, &hull).0 - peri).abs() < 1e-9);
+
+        let m = rng.rand(10) as usize + 1;
+        let ws: Vec<Point<i64>> = (0..m)
+            .map(|_| Point(rng.rand(41) as i64 + 30, rng.rand(41) as i64 - 20))
+            .collect();
+        let ps: Vec<Point<i64>> = hull.iter().map(|&i| vs[i]).collect();
+        let qs: Vec<Point<i64>> = convex_hull(&ws).into_iter().map(|i| ws[i]).collect();
+        let brute = vs
+            .iter()
+            .flat_map(|&v| ws.iter().map(move |&w| (v - w).norm2()))
+            .max()
+            .unwrap();
+        assert_eq!(max_distance_convex(&ps, &qs), brute);
+    }
+}
+
+#[test]
+fn test_half_plane_intersection() {
+    let l = |a: f64, b: f64, c: f64, d: f64| Line(Vector2D(a, b), Vector2D(c, d));
+    let sq = vec![
+        l(0.0, 0.0, 1.0, 0.0),
+        l(2.0, 0.0, 2.0, 1.0),
+        l(2.0, 2.0, 1.0, 2.0),
+        l(0.0, 2.0, 0.0, 1.0),
+        l(-5.0, -5.0, 5.0, -5.0),
+    ];
+    match half_plane_intersection(&sq) {
+        HalfPlaneIntersection::Bounded(ps) => {
+            let ps: Vec<Point<f64>> = ps.into_iter().map(|p| p.into()).collect();
+            assert_eq!(polygon_area2(&ps), 8.0);
+        }
+        r => panic!("{:?}", r),
+    }
+    let mut empty = sq.clone();
+    empty.push(l(3.0, 0.0, 3.0, -1.0));
+    assert_eq!(
+        half_plane_intersection(&empty),
+        HalfPlaneIntersection::Empty
+    );
+    assert_eq!(
+        half_plane_intersection(&sq[..3]),
+        HalfPlaneIntersection::Unbounded
+    );
+    assert_eq!(
+        half_plane_intersection(&sq[..1]),
+        HalfPlaneIntersection::Unbounded
+    );
+    assert_eq!(
+        half_plane_intersection(&[]),
+        HalfPlaneIntersection::Unbounded
+    );
+    // strips
+    assert_eq!(
+        half_plane_intersection(&[sq[0], sq[2]]),
+        HalfPlaneIntersection::Unbounded
+    );
+    assert_eq!(
+        half_plane_intersection(&[sq[0], l(1.0, -1.0, 0.0, -1.0)]),
+        HalfPlaneIntersection::Empty
+    );
+    // no interior
+    let tri = vec![
+        l(0.0, 0.0, 1.0, 0.0),
+        l(0.0, 0.0, 0.0, -1.0),
+        l(0.0, 0.0, -1.0, 1.0),
+    ];
+    assert_eq!(half_plane_intersection(&tri), HalfPlaneIntersection::Empty);
+    assert_eq!(
+        half_plane_intersection(&[sq[0], l(1.0, 0.0, 0.0, 0.0)]),
+        HalfPlaneIntersection::Empty
+    );
+}
+
+#[test]
+fn test_half_plane_intersection_random() {
+    let mut rng = Xorshift::with_seed(5);
+    let b = 1e5;
+    let bx = vec![Point(-b, -b), Point(b, -b), Point(b, b), Point(-b, b)];
+    for _ in 0..2000 {
+        let n = rng.rand(7) as usize + 1;
+        let mut r = || rng.rand(21) as f64 - 10.0;
+        let hs: Vec<Line> = (0..n)
+            .map(|_| {
+                let p = Vector2D(r(), r());
+                let mut d = Vector2D(r(), r());
+                while d == Vector2D(0.0, 0.0) {
+                    d = Vector2D(r(), r());
+                }
+                Line(p, p + d)
+            })
+            .collect();
+        let mut cut = bx.clone();
+        for h in &hs {
+            cut = convex_cut(&cut, h.0.into(), h.1.into());
+        }
+        let area = if cut.len() < 3 {
+            0.0
+        } else {
+            polygon_area2(&cut) / 2.0
+        };
+        match half_plane_intersection(&hs) {
+            HalfPlaneIntersection::Empty => assert!(area < 1e-3),
+            HalfPlaneIntersection::Unbounded => assert!(area > 1e3),
+            HalfPlaneIntersection::Bounded(ps) => {
+                let ps: Vec<Point<f64>> = ps.into_iter().map(|p| p.into()).collect();
+                assert!((polygon_area2(&ps) / 2.0 - area).abs() < 1e-6);
+            }
+        }
+    }
+}
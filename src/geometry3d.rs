@@ -0,0 +1,271 @@
+use crate::geometry::EPS;
+
+#[snippet = "Vector3D"]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vector3D(pub f64, pub f64, pub f64);
+
+#[snippet = "Vector3D"]
+#[allow(dead_code)]
+impl Vector3D {
+    pub fn dot(self, other: Vector3D) -> f64 {
+        self.0 * other.0 + self.1 * other.1 + self.2 * other.2
+    }
+    pub fn cross(self, other: Vector3D) -> Vector3D {
+        Vector3D(
+            self.1 * other.2 - self.2 * other.1,
+            self.2 * other.0 - self.0 * other.2,
+            self.0 * other.1 - self.1 * other.0,
+        )
+    }
+    pub fn len(&self) -> f64 {
+        self.dot(*self).sqrt()
+    }
+    pub fn unit(self) -> Vector3D {
+        self / self.len()
+    }
+}
+
+#[snippet = "Vector3D"]
+impl std::ops::Add for Vector3D {
+    type Output = Vector3D;
+    fn add(self, rhs: Vector3D) -> Vector3D {
+        Vector3D(self.0 + rhs.0, self.1 + rhs.1, self.2 + rhs.2)
+    }
+}
+
+#[snippet = "Vector3D"]
+impl std::ops::Sub for Vector3D {
+    type Output = Vector3D;
+    fn sub(self, rhs: Vector3D) -> Vector3D {
+        Vector3D(self.0 - rhs.0, self.1 - rhs.1, self.2 - rhs.2)
+    }
+}
+
+#[snippet = "Vector3D"]
+impl std::ops::Mul<f64> for Vector3D {
+    type Output = Vector3D;
+    fn mul(self, rhs: f64) -> Vector3D {
+        Vector3D(self.0 * rhs, self.1 * rhs, self.2 * rhs)
+    }
+}
+
+#[snippet = "Vector3D"]
+impl std::ops::Div<f64> for Vector3D {
+    type Output = Vector3D;
+    fn div(self, rhs: f64) -> Vector3D {
+        Vector3D(self.0 / rhs, self.1 / rhs, self.2 / rhs)
+    }
+}
+
+#[snippet = "Vector3D"]
+impl std::ops::Neg for Vector3D {
+    type Output = Vector3D;
+    fn neg(self) -> Vector3D {
+        Vector3D(-self.0, -self.1, -self.2)
+    }
+}
+
+#[snippet = "Plane"]
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// points p with n.dot(p) == d. n is not normalized
+pub struct Plane {
+    pub n: Vector3D,
+    pub d: f64,
+}
+
+#[snippet = "Plane"]
+#[allow(dead_code)]
+impl Plane {
+    #[doc = "through a, b, c. the normal points to the side from which a, b, c look counter clockwise"]
+    pub fn new(a: Vector3D, b: Vector3D, c: Vector3D) -> Plane {
+        let n = (b - a).cross(c - a);
+        Plane { n: n, d: n.dot(a) }
+    }
+    #[doc = "positive on the side n points to"]
+    pub fn signed_distance(&self, p: Vector3D) -> f64 {
+        (self.n.dot(p) - self.d) / self.n.len()
+    }
+    pub fn distance(&self, p: Vector3D) -> f64 {
+        self.signed_distance(p).abs()
+    }
+    pub fn projection(&self, p: Vector3D) -> Vector3D {
+        p - self.n * ((self.n.dot(p) - self.d) / self.n.dot(self.n))
+    }
+    #[doc = "intersection with the line through a and b. None if parallel"]
+    pub fn cross_line(&self, a: Vector3D, b: Vector3D) -> Option<Vector3D> {
+        let den = self.n.dot(b - a);
+        if den.abs() < EPS * self.n.len() * (b - a).len() {
+            return None;
+        }
+        Some(a + (b - a) * ((self.d - self.n.dot(a)) / den))
+    }
+}
+
+#[snippet = "convex_hull3d"]
+#[allow(dead_code)]
+#[doc = "faces of the convex hull as triangles, counter clockwise seen from outside. empty if all points are coplanar. exact for |coordinate| <= 1e9. O(n^2)"]
+pub fn convex_hull3d(ps: &[[i64; 3]]) -> Vec<[usize; 3]> {
+    let n = ps.len();
+    let sub = |a: usize, b: usize| {
+        [
+            ps[a][0] as i128 - ps[b][0] as i128,
+            ps[a][1] as i128 - ps[b][1] as i128,
+            ps[a][2] as i128 - ps[b][2] as i128,
+        ]
+    };
+    let cross = |u: [i128; 3], v: [i128; 3]| {
+        [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]]
+    };
+    // > 0 iff p is on the outer side of face (a, b, c)
+    let vol = |a: usize, b: usize, c: usize, p: usize| {
+        let w = cross(sub(b, a), sub(c, a));
+        let d = sub(p, a);
+        w[0] * d[0] + w[1] * d[1] + w[2] * d[2]
+    };
+    let i1 = match (1..n).find(|&i| ps[i] != ps[0]) {
+        Some(i) => i,
+        None => return vec![],
+    };
+    let i2 = match (1..n).find(|&i| cross(sub(i1, 0), sub(i, 0)) != [0; 3]) {
+        Some(i) => i,
+        None => return vec![],
+    };
+    let i3 = match (1..n).find(|&i| vol(0, i1, i2, i) != 0) {
+        Some(i) => i,
+        None => return vec![],
+    };
+    let (i1, i2) = if vol(0, i1, i2, i3) > 0 { (i2, i1) } else { (i1, i2) };
+    let mut faces = vec![[0, i1, i2], [0, i2, i3], [0, i3, i1], [i1, i3, i2]];
+    for p in 0..n {
+        if p == 0 || p == i1 || p == i2 || p == i3 {
+            continue;
+        }
+        let vis: Vec<bool> = faces.iter().map(|f| vol(f[0], f[1], f[2], p) > 0).collect();
+        if !vis.iter().any(|&v| v) {
+            continue;
+        }
+        let mut edges = std::collections::HashSet::new();
+        for (f, &v) in faces.iter().zip(&vis) {
+            if v {
+                for k in 0..3 {
+                    edges.insert((f[k], f[(k + 1) % 3]));
+                }
+            }
+        }
+        let mut next = vec![];
+        for (f, &v) in faces.iter().zip(&vis) {
+            if !v {
+                next.push(*f);
+                continue;
+            }
+            for k in 0..3 {
+                let (a, b) = (f[k], f[(k + 1) % 3]);
+                // the horizon separates a visible face from an invisible one
+                if !edges.contains(&(b, a)) {
+                    next.push([a, b, p]);
+                }
+            }
+        }
+        faces = next;
+    }
+    faces
+}
+
+#[snippet = "convex_hull3d"]
+#[allow(dead_code)]
+pub fn hull3d_volume(ps: &[[i64; 3]], faces: &[[usize; 3]]) -> f64 {
+    let mut s = 0i128;
+    for f in faces {
+        let (a, b, c) = (ps[f[0]], ps[f[1]], ps[f[2]]);
+        let (a, b, c) = (
+            [a[0] as i128, a[1] as i128, a[2] as i128],
+            [b[0] as i128, b[1] as i128, b[2] as i128],
+            [c[0] as i128, c[1] as i128, c[2] as i128],
+        );
+        // a . (b x c)
+        s += a[0] * (b[1] * c[2] - b[2] * c[1]) + a[1] * (b[2] * c[0] - b[0] * c[2]) + a[2] * (b[0] * c[1] - b[1] * c[0]);
+    }
+    s as f64 / 6.0
+}
+
+#[snippet = "convex_hull3d"]
+#[allow(dead_code)]
+pub fn hull3d_surface_area(ps: &[[i64; 3]], faces: &[[usize; 3]]) -> f64 {
+    let v = |i: usize| Vector3D(ps[i][0] as f64, ps[i][1] as f64, ps[i][2] as f64);
+    faces
+        .iter()
+        .map(|f| (v(f[1]) - v(f[0])).cross(v(f[2]) - v(f[0])).len() / 2.0)
+        .sum()
+}
+
+#[test]
+fn test_plane() {
+    let a = Vector3D(1.0, 0.0, 0.0);
+    let b = Vector3D(0.0, 1.0, 0.0);
+    let c = Vector3D(0.0, 0.0, 1.0);
+    let pl = Plane::new(a, b, c);
+    let o = Vector3D(0.0, 0.0, 0.0);
+    assert!((pl.signed_distance(o) + 1.0 / 3f64.sqrt()).abs() < 1e-9);
+    assert!((pl.distance(Vector3D(1.0, 1.0, 1.0)) - 2.0 / 3f64.sqrt()).abs() < 1e-9);
+    let h = pl.projection(o);
+    assert!((h - Vector3D(1.0, 1.0, 1.0) / 3.0).len() < 1e-9);
+    let x = pl.cross_line(o, Vector3D(2.0, 0.0, 0.0)).unwrap();
+    assert!((x - a).len() < 1e-9);
+    let x = pl.cross_line(Vector3D(0.0, 0.0, -1.0), Vector3D(1.0, 1.0, 1.0)).unwrap();
+    assert!(pl.distance(x) < 1e-9);
+    assert!((x - Vector3D(0.5, 0.5, 0.0)).len() < 1e-9);
+    assert_eq!(pl.cross_line(o, b - a), None);
+}
+
+#[test]
+fn test_convex_hull3d() {
+    use crate::xorshift::Xorshift;
+    // 3x3x3 lattice: many coplanar and collinear points
+    let mut ps = vec![];
+    for x in 0..3 {
+        for y in 0..3 {
+            for z in 0..3 {
+                ps.push([x, y, z]);
+            }
+        }
+    }
+    let faces = convex_hull3d(&ps);
+    assert_eq!(hull3d_volume(&ps, &faces), 8.0);
+    assert!((hull3d_surface_area(&ps, &faces) - 24.0).abs() < 1e-9);
+    assert!(convex_hull3d(&[[0, 0, 0], [1, 1, 1], [2, 2, 2], [0, 1, 0]]).is_empty());
+
+    let mut rng = Xorshift::new();
+    for _ in 0..100 {
+        let n = rng.rand(30) as usize + 4;
+        let w = rng.rand(10) + 2;
+        let ps: Vec<[i64; 3]> = (0..n)
+            .map(|_| [rng.rand(w) as i64, rng.rand(w) as i64, rng.rand(w) as i64])
+            .collect();
+        let faces = convex_hull3d(&ps);
+        if faces.is_empty() {
+            continue;
+        }
+        let v = |i: usize| Vector3D(ps[i][0] as f64, ps[i][1] as f64, ps[i][2] as f64);
+        let mut edges = std::collections::HashSet::new();
+        for f in &faces {
+            // every point is on the inner side
+            let pl = Plane::new(v(f[0]), v(f[1]), v(f[2]));
+            assert!(pl.n.len() > 0.0);
+            for i in 0..n {
+                assert!(pl.n.dot(v(i)) - pl.d <= 0.0);
+            }
+            for k in 0..3 {
+                assert!(edges.insert((f[k], f[(k + 1) % 3])));
+            }
+        }
+        // closed surface
+        for &(a, b) in &edges {
+            assert!(edges.contains(&(b, a)));
+        }
+        assert!(hull3d_volume(&ps, &faces) > 0.0);
+    }
+    let ps = vec![[-1_000_000_000, -1_000_000_000, -1_000_000_000], [1_000_000_000, 0, 0], [0, 1_000_000_000, 0], [0, 0, 1_000_000_000], [1, 1, 1]];
+    let faces = convex_hull3d(&ps);
+    assert_eq!(faces.len(), 4);
+    assert!((hull3d_volume(&ps, &faces) - 4e27 / 6.0).abs() < 1e15);
+}
@@ -0,0 +1,256 @@
+use crate::bitset::BitSet;
+
+#[snippet = "GF2"]
+#[doc = "reduce the first ncols columns of a to reduced row echelon form over GF(2). returns pivot columns. O(m n ncols / 64)"]
+pub fn gf2_gauss_jordan(a: &mut [BitSet], ncols: usize) -> Vec<usize> {
+    let m = a.len();
+    let mut pivots = vec![];
+    let mut r = 0;
+    for c in 0..ncols {
+        if r == m {
+            break;
+        }
+        let p = match (r..m).find(|&i| a[i][c]) {
+            Some(p) => p,
+            None => continue,
+        };
+        a.swap(p, r);
+        let pr = a[r].clone();
+        for i in 0..m {
+            if i != r && a[i][c] {
+                a[i] ^= &pr;
+            }
+        }
+        pivots.push(c);
+        r += 1;
+    }
+    pivots
+}
+
+#[snippet = "GF2"]
+#[allow(dead_code)]
+pub fn gf2_rank(a: &[BitSet], n: usize) -> usize {
+    let mut a = a.to_vec();
+    gf2_gauss_jordan(&mut a, n).len()
+}
+
+#[snippet = "GF2"]
+#[allow(dead_code)]
+#[doc = "Ax=b where rows of A are BitSets of n variables. returns (x0, kernel basis) or None if inconsistent"]
+pub fn gf2_solve(a: &[BitSet], b: &[bool], n: usize) -> Option<(BitSet, Vec<BitSet>)> {
+    let m = a.len();
+    assert!(b.len() == m);
+    let mut aug = vec![BitSet::new(n + 1); m];
+    for i in 0..m {
+        for j in 0..n {
+            if a[i][j] {
+                aug[i].set(j, true);
+            }
+        }
+        aug[i].set(n, b[i]);
+    }
+    let pivots = gf2_gauss_jordan(&mut aug, n);
+    let rank = pivots.len();
+    if (rank..m).any(|i| aug[i][n]) {
+        return None;
+    }
+    let mut x = BitSet::new(n);
+    let mut is_pivot = vec![false; n];
+    for i in 0..rank {
+        x.set(pivots[i], aug[i][n]);
+        is_pivot[pivots[i]] = true;
+    }
+    let mut kernel = vec![];
+    for f in 0..n {
+        if is_pivot[f] {
+            continue;
+        }
+        let mut y = BitSet::new(n);
+        y.set(f, true);
+        for i in 0..rank {
+            if aug[i][f] {
+                y.set(pivots[i], true);
+            }
+        }
+        kernel.push(y);
+    }
+    Some((x, kernel))
+}
+
+#[snippet = "XorBasis"]
+#[derive(Clone, Debug)]
+/// Linear basis of u64 values over GF(2)
+pub struct XorBasis {
+    // b[i] is 0 or has its highest bit at i
+    b: [u64; 64],
+    rank: usize,
+}
+
+#[snippet = "XorBasis"]
+#[allow(dead_code)]
+impl XorBasis {
+    pub fn new() -> XorBasis {
+        XorBasis { b: [0; 64], rank: 0 }
+    }
+    pub fn len(&self) -> usize {
+        self.rank
+    }
+    #[doc = "false if x is already in the span. O(64)"]
+    pub fn insert(&mut self, x: u64) -> bool {
+        let mut x = x;
+        for i in (0..64).rev() {
+            if x >> i & 1 == 0 {
+                continue;
+            }
+            if self.b[i] == 0 {
+                self.b[i] = x;
+                self.rank += 1;
+                return true;
+            }
+            x ^= self.b[i];
+        }
+        false
+    }
+    pub fn contains(&self, x: u64) -> bool {
+        let mut x = x;
+        for i in (0..64).rev() {
+            if x >> i & 1 == 1 {
+                x ^= self.b[i];
+            }
+        }
+        x == 0
+    }
+    #[doc = "max x^v for v in the span"]
+    pub fn max_xor(&self, x: u64) -> u64 {
+        let mut x = x;
+        for i in (0..64).rev() {
+            x = std::cmp::max(x, x ^ self.b[i]);
+        }
+        x
+    }
+    #[doc = "min x^v for v in the span"]
+    pub fn min_xor(&self, x: u64) -> u64 {
+        let mut x = x;
+        for i in (0..64).rev() {
+            x = std::cmp::min(x, x ^ self.b[i]);
+        }
+        x
+    }
+    #[doc = "k-th (0-indexed) smallest value in the span. the 0th is 0. O(64^2)"]
+    pub fn kth(&self, k: u64) -> Option<u64> {
+        if self.rank < 64 && k >> self.rank > 0 {
+            return None;
+        }
+        let mut b = self.b;
+        for i in 0..64 {
+            for j in (0..i).rev() {
+                if b[i] >> j & 1 == 1 {
+                    b[i] ^= b[j];
+                }
+            }
+        }
+        let mut res = 0;
+        let mut t = 0;
+        for i in 0..64 {
+            if b[i] == 0 {
+                continue;
+            }
+            if k >> t & 1 == 1 {
+                res ^= b[i];
+            }
+            t += 1;
+        }
+        Some(res)
+    }
+}
+
+#[test]
+fn test_gf2_solve_small() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    for _ in 0..200 {
+        let m = rng.rand(6) as usize + 1;
+        let n = rng.rand(6) as usize + 1;
+        let mut a = vec![BitSet::new(n); m];
+        let mut b = vec![false; m];
+        for i in 0..m {
+            for j in 0..n {
+                a[i].set(j, rng.rand(2) == 1);
+            }
+            b[i] = rng.rand(2) == 1;
+        }
+        let eval = |x: u64| (0..m).all(|i| {
+            let s = (0..n).filter(|&j| a[i][j] && x >> j & 1 == 1).count();
+            (s % 2 == 1) == b[i]
+        });
+        let n_sol = (0..1u64 << n).filter(|&x| eval(x)).count();
+        let to_u64 = |x: &BitSet| (0..n).filter(|&j| x[j]).fold(0u64, |acc, j| acc | 1 << j);
+        match gf2_solve(&a, &b, n) {
+            None => assert_eq!(n_sol, 0),
+            Some((x, kernel)) => {
+                assert!(eval(to_u64(&x)));
+                assert_eq!(kernel.len(), n - gf2_rank(&a, n));
+                assert_eq!(n_sol, 1 << kernel.len());
+                for y in &kernel {
+                    assert!(eval(to_u64(&x) ^ to_u64(y)));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_gf2_solve_large() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    let n = 2000;
+    let mut a = vec![BitSet::new(n); n];
+    let mut ans = BitSet::new(n);
+    for j in 0..n {
+        ans.set(j, rng.rand(2) == 1);
+    }
+    let mut b = vec![false; n];
+    for i in 0..n {
+        for j in 0..n {
+            a[i].set(j, rng.rand(2) == 1);
+        }
+        b[i] = (a[i].clone() & &ans).count_ones() % 2 == 1;
+    }
+    let (x, kernel) = gf2_solve(&a, &b, n).unwrap();
+    for i in 0..n {
+        assert_eq!((a[i].clone() & &x).count_ones() % 2 == 1, b[i]);
+    }
+    for y in &kernel {
+        for i in 0..n {
+            assert_eq!((a[i].clone() & y).count_ones() % 2, 0);
+        }
+    }
+}
+
+#[test]
+fn test_xor_basis() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    let mut basis = XorBasis::new();
+    let mut span = vec![0u64];
+    for _ in 0..8 {
+        let x = rng.rand(1 << 10);
+        let inserted = basis.insert(x);
+        assert_eq!(inserted, !span.contains(&x));
+        if inserted {
+            let ys: Vec<u64> = span.iter().map(|&y| y ^ x).collect();
+            span.extend(ys);
+        }
+    }
+    span.sort();
+    assert_eq!(span.len(), 1 << basis.len());
+    for k in 0..span.len() {
+        assert_eq!(basis.kth(k as u64), Some(span[k]));
+    }
+    assert_eq!(basis.kth(span.len() as u64), None);
+    for x in 0..1 << 10 {
+        assert_eq!(basis.contains(x), span.binary_search(&x).is_ok());
+        assert_eq!(basis.max_xor(x), span.iter().map(|&y| x ^ y).max().unwrap());
+        assert_eq!(basis.min_xor(x), span.iter().map(|&y| x ^ y).min().unwrap());
+    }
+}
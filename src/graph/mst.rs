@@ -36,18 +36,18 @@ mod prim {
     }
 }
 
-mod kraskal {
+pub mod kraskal {
     use crate::union_find;
 
     #[snippet = "kraskal"]
-    struct Edge {
-        u: usize,
-        v: usize,
-        cost: u64
+    pub struct Edge {
+        pub u: usize,
+        pub v: usize,
+        pub cost: u64
     }
     #[snippet = "kraskal"]
     #[doc = "es: undirected edges. O(ElogV)"]
-    fn kraskal(n: usize, es: &mut [Edge]) -> u64 {
+    pub fn kraskal(n: usize, es: &mut [Edge]) -> u64 {
         es.sort_by(|a, b| {
             a.cost.cmp(&b.cost)
         });
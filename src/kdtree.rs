@@ -0,0 +1,228 @@
+use crate::geometry::{Coord, Point};
+
+#[snippet = "KDTree"]
+#[allow(dead_code)]
+/// Static 2D k-d tree. Node of [l, r) is stored at (l + r) / 2.
+pub struct KDTree<T> {
+    ps: Vec<Point<T>>,
+    id: Vec<usize>,
+    // bounding box of the subtree
+    lo: Vec<Point<T>>,
+    hi: Vec<Point<T>>,
+    split_x: Vec<bool>,
+}
+
+#[snippet = "KDTree"]
+#[allow(dead_code)]
+impl<T: Coord> KDTree<T> {
+    #[doc = "expected O(n log n)"]
+    pub fn new(ps: &[Point<T>]) -> KDTree<T> {
+        let n = ps.len();
+        let mut t = KDTree {
+            ps: vec![],
+            id: vec![],
+            lo: ps.to_vec(),
+            hi: ps.to_vec(),
+            split_x: vec![true; n],
+        };
+        let mut v: Vec<(Point<T>, usize)> = ps.iter().cloned().zip(0..n).collect();
+        t.build(&mut v, 0);
+        t.ps = v.iter().map(|p| p.0).collect();
+        t.id = v.iter().map(|p| p.1).collect();
+        t
+    }
+    fn build(&mut self, v: &mut [(Point<T>, usize)], l: usize) {
+        if v.is_empty() {
+            return;
+        }
+        let (mut lo, mut hi) = (v[0].0, v[0].0);
+        for &(p, _) in v.iter() {
+            if p.0 < lo.0 { lo.0 = p.0; }
+            if p.1 < lo.1 { lo.1 = p.1; }
+            if p.0 > hi.0 { hi.0 = p.0; }
+            if p.1 > hi.1 { hi.1 = p.1; }
+        }
+        let m = v.len() / 2;
+        let sx = hi.0 - lo.0 >= hi.1 - lo.1;
+        self.lo[l + m] = lo;
+        self.hi[l + m] = hi;
+        self.split_x[l + m] = sx;
+        let key = |p: &Point<T>| if sx { (p.0, p.1) } else { (p.1, p.0) };
+        Self::select(v, m, key);
+        let (left, right) = v.split_at_mut(m);
+        self.build(left, l);
+        self.build(&mut right[1..], l + m + 1);
+    }
+    // move the m-th smallest by key to v[m], smaller ones before and larger ones after. expected O(n)
+    fn select<F: Fn(&Point<T>) -> (T, T)>(v: &mut [(Point<T>, usize)], m: usize, key: F) {
+        let (mut l, mut r) = (0, v.len());
+        while r - l > 1 {
+            let pivot = key(&v[(l + r) / 2].0);
+            // three-way partition of [l, r) into < pivot, == pivot and > pivot
+            let (mut lt, mut i, mut gt) = (l, l, r);
+            while i < gt {
+                let k = key(&v[i].0);
+                if k < pivot {
+                    v.swap(lt, i);
+                    lt += 1;
+                    i += 1;
+                } else if k > pivot {
+                    gt -= 1;
+                    v.swap(i, gt);
+                } else {
+                    i += 1;
+                }
+            }
+            if m < lt {
+                r = lt;
+            } else if m >= gt {
+                l = gt;
+            } else {
+                return;
+            }
+        }
+    }
+    fn dist2(a: Point<T>, b: Point<T>) -> T {
+        (a - b).norm2()
+    }
+    // squared distance from q to the bounding box of node k
+    fn box_dist2(&self, k: usize, q: Point<T>) -> T {
+        let zero = T::default();
+        let d = |x: T, lo: T, hi: T| if x < lo { lo - x } else if x > hi { x - hi } else { zero };
+        let dx = d(q.0, self.lo[k].0, self.hi[k].0);
+        let dy = d(q.1, self.lo[k].1, self.hi[k].1);
+        dx * dx + dy * dy
+    }
+    // best is sorted by distance and holds at most kk elements
+    fn do_nearest(&self, l: usize, r: usize, q: Point<T>, kk: usize, best: &mut Vec<(T, usize)>) {
+        if l >= r {
+            return;
+        }
+        let m = (l + r) / 2;
+        if best.len() == kk && !(self.box_dist2(m, q) < best[kk - 1].0) {
+            return;
+        }
+        let d = Self::dist2(self.ps[m], q);
+        if best.len() < kk || d < best[kk - 1].0 {
+            if best.len() == kk {
+                best.pop();
+            }
+            let mut i = best.len();
+            while i > 0 && d < best[i - 1].0 {
+                i -= 1;
+            }
+            best.insert(i, (d, self.id[m]));
+        }
+        let first_left = if self.split_x[m] {
+            q.0 < self.ps[m].0
+        } else {
+            q.1 < self.ps[m].1
+        };
+        if first_left {
+            self.do_nearest(l, m, q, kk, best);
+            self.do_nearest(m + 1, r, q, kk, best);
+        } else {
+            self.do_nearest(m + 1, r, q, kk, best);
+            self.do_nearest(l, m, q, kk, best);
+        }
+    }
+    #[doc = "(squared distance, index) of the nearest point. None if empty"]
+    pub fn nearest(&self, q: Point<T>) -> Option<(T, usize)> {
+        self.k_nearest(q, 1).pop()
+    }
+    #[doc = "(squared distance, index) of the k nearest points in increasing order of distance"]
+    pub fn k_nearest(&self, q: Point<T>, k: usize) -> Vec<(T, usize)> {
+        let mut best = vec![];
+        if k > 0 {
+            self.do_nearest(0, self.ps.len(), q, k, &mut best);
+        }
+        best
+    }
+    fn do_rect(&self, l: usize, r: usize, lo: Point<T>, hi: Point<T>, report: &mut Option<&mut Vec<usize>>) -> usize {
+        if l >= r {
+            return 0;
+        }
+        let m = (l + r) / 2;
+        let (blo, bhi) = (self.lo[m], self.hi[m]);
+        if bhi.0 < lo.0 || hi.0 < blo.0 || bhi.1 < lo.1 || hi.1 < blo.1 {
+            return 0;
+        }
+        if report.is_none() && lo.0 <= blo.0 && bhi.0 <= hi.0 && lo.1 <= blo.1 && bhi.1 <= hi.1 {
+            return r - l;
+        }
+        let p = self.ps[m];
+        let mut res = 0;
+        if lo.0 <= p.0 && p.0 <= hi.0 && lo.1 <= p.1 && p.1 <= hi.1 {
+            if let Some(v) = report.as_mut() {
+                v.push(self.id[m]);
+            }
+            res += 1;
+        }
+        res + self.do_rect(l, m, lo, hi, report) + self.do_rect(m + 1, r, lo, hi, report)
+    }
+    #[doc = "number of points in [lo.0, hi.0] x [lo.1, hi.1]. about O(sqrt n)"]
+    pub fn count(&self, lo: Point<T>, hi: Point<T>) -> usize {
+        self.do_rect(0, self.ps.len(), lo, hi, &mut None)
+    }
+    #[doc = "indices of points in [lo.0, hi.0] x [lo.1, hi.1]. about O(sqrt n + k)"]
+    pub fn report(&self, lo: Point<T>, hi: Point<T>) -> Vec<usize> {
+        let mut res = vec![];
+        self.do_rect(0, self.ps.len(), lo, hi, &mut Some(&mut res));
+        res
+    }
+}
+
+#[test]
+fn test_kdtree_i64() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    assert_eq!(KDTree::<i64>::new(&[]).nearest(Point(0, 0)), None);
+    for _ in 0..50 {
+        let n = rng.rand(200) as usize + 1;
+        let w = rng.rand(100) + 1;
+        let p = |rng: &mut Xorshift| Point(rng.rand(w) as i64, rng.rand(w) as i64);
+        let ps: Vec<_> = (0..n).map(|_| p(&mut rng)).collect();
+        let t = KDTree::new(&ps);
+        for _ in 0..50 {
+            let q = p(&mut rng);
+            let mut ds: Vec<(i64, usize)> = (0..n).map(|i| ((ps[i] - q).norm2(), i)).collect();
+            ds.sort();
+            assert_eq!(t.nearest(q).unwrap().0, ds[0].0);
+            let k = rng.rand(n as u64 + 2) as usize;
+            let res = t.k_nearest(q, k);
+            assert_eq!(res.len(), k.min(n));
+            for (i, &(d, j)) in res.iter().enumerate() {
+                assert_eq!(d, ds[i].0);
+                assert_eq!(d, (ps[j] - q).norm2());
+            }
+            let (a, b) = (p(&mut rng), p(&mut rng));
+            let lo = Point(a.0.min(b.0), a.1.min(b.1));
+            let hi = Point(a.0.max(b.0), a.1.max(b.1));
+            let expected: Vec<usize> = (0..n)
+                .filter(|&i| lo.0 <= ps[i].0 && ps[i].0 <= hi.0 && lo.1 <= ps[i].1 && ps[i].1 <= hi.1)
+                .collect();
+            assert_eq!(t.count(lo, hi), expected.len());
+            let mut got = t.report(lo, hi);
+            got.sort();
+            assert_eq!(got, expected);
+        }
+    }
+}
+
+#[test]
+fn test_kdtree_f64() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    let n = 1000;
+    let ps: Vec<_> = (0..n).map(|_| Point(rng.randf() * 100.0, rng.randf())).collect();
+    let t = KDTree::new(&ps);
+    for _ in 0..100 {
+        let q = Point(rng.randf() * 100.0, rng.randf());
+        let best = (0..n).map(|i| (ps[i] - q).norm2()).fold(std::f64::MAX, f64::min);
+        assert_eq!(t.nearest(q).unwrap().0, best);
+        let (x, y) = (rng.randf() * 100.0, rng.randf());
+        let (lo, hi) = (Point(x, y), Point(x + 10.0, y + 0.2));
+        let cnt = ps.iter().filter(|p| lo.0 <= p.0 && p.0 <= hi.0 && lo.1 <= p.1 && p.1 <= hi.1).count();
+        assert_eq!(t.count(lo, hi), cnt);
+    }
+}
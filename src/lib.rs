@@ -49,4 +49,13 @@ pub mod combination;
 pub mod treap;
 pub mod syakutori;
 pub mod macros;
-pub mod wavelet_matrix;
\ No newline at end of file
+pub mod wavelet_matrix;
+pub mod gf2;
+pub mod delaunay;
+pub mod sweep;
+pub mod geometry3d;
+pub mod kdtree;
+pub mod manhattan;
+pub mod dynamic_seg;
+pub mod persistent_seg;
+pub mod seg_beats;
\ No newline at end of file
@@ -0,0 +1,136 @@
+use crate::fenwick::BITGeneric;
+use crate::geometry::Point;
+use crate::graph::mst::kraskal::{kraskal, Edge};
+
+#[snippet = "manhattan_mst"]
+#[allow(dead_code)]
+#[doc = "at most 4n edges containing a Manhattan minimum spanning tree. O(n log n)"]
+pub fn manhattan_mst_edges(ps: &[Point<i64>]) -> Vec<Edge> {
+    let n = ps.len();
+    let mut res = vec![];
+    let mut qs = ps.to_vec();
+    for k in 0..4 {
+        // connect i to the nearest j with 0 <= dx <= dy, i.e. x_j >= x_i and y_j - x_j >= y_i - x_i.
+        // the four transforms (x, y), (y, x), (-x, y), (y, -x) cover the other octants up to direction
+        if k == 1 || k == 3 {
+            for p in &mut qs {
+                *p = Point(p.1, p.0);
+            }
+        }
+        if k == 2 {
+            for p in &mut qs {
+                *p = Point(-p.1, p.0);
+            }
+        }
+        let mut keys: Vec<i64> = qs.iter().map(|p| p.1 - p.0).collect();
+        keys.sort();
+        keys.dedup();
+        let mut idx: Vec<usize> = (0..n).collect();
+        idx.sort_by_key(|&i| (-qs[i].0, -qs[i].1));
+        // prefix min over keys in decreasing order
+        let mut bit = BITGeneric::new(keys.len(), &(std::i64::MAX, n), |a: &mut (i64, usize), b: &(i64, usize)| {
+            if *b < *a {
+                *a = *b;
+            }
+        });
+        for &i in &idx {
+            let p = qs[i];
+            let r = keys.len() - keys.binary_search(&(p.1 - p.0)).unwrap();
+            let (s, j) = bit.sum(r);
+            if j < n {
+                res.push(Edge { u: i, v: j, cost: (s - p.0 - p.1) as u64 });
+            }
+            bit.add(r, &(p.0 + p.1, i));
+        }
+    }
+    res
+}
+
+#[snippet = "manhattan_mst"]
+#[allow(dead_code)]
+#[doc = "total weight of a Manhattan minimum spanning tree. O(n log n)"]
+pub fn manhattan_mst(ps: &[Point<i64>]) -> u64 {
+    kraskal(ps.len(), &mut manhattan_mst_edges(ps))
+}
+
+#[snippet = "manhattan_rotate"]
+#[doc = "|dx| + |dy| becomes max(|du|, |dv|)"]
+pub fn rotate45(p: Point<i64>) -> Point<i64> {
+    Point(p.0 + p.1, p.0 - p.1)
+}
+
+#[snippet = "manhattan_rotate"]
+/// Farthest Manhattan distance from a query point to a fixed set
+pub struct ManhattanFarthest {
+    lo: Point<i64>,
+    hi: Point<i64>,
+}
+
+#[snippet = "manhattan_rotate"]
+#[allow(dead_code)]
+impl ManhattanFarthest {
+    #[doc = "ps must not be empty"]
+    pub fn new(ps: &[Point<i64>]) -> ManhattanFarthest {
+        let q = rotate45(ps[0]);
+        let mut res = ManhattanFarthest { lo: q, hi: q };
+        for &p in ps {
+            res.insert(p);
+        }
+        res
+    }
+    pub fn insert(&mut self, p: Point<i64>) {
+        let q = rotate45(p);
+        self.lo = Point(self.lo.0.min(q.0), self.lo.1.min(q.1));
+        self.hi = Point(self.hi.0.max(q.0), self.hi.1.max(q.1));
+    }
+    #[doc = "O(1)"]
+    pub fn query(&self, p: Point<i64>) -> i64 {
+        let q = rotate45(p);
+        (q.0 - self.lo.0).max(self.hi.0 - q.0).max(q.1 - self.lo.1).max(self.hi.1 - q.1)
+    }
+    #[doc = "max distance between two points of the set"]
+    pub fn diameter(&self) -> i64 {
+        (self.hi.0 - self.lo.0).max(self.hi.1 - self.lo.1)
+    }
+}
+
+#[test]
+fn test_manhattan_mst() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    for _ in 0..200 {
+        let n = rng.rand(40) as usize + 1;
+        let w = rng.rand(50) + 1;
+        let ps: Vec<Point<i64>> = (0..n).map(|_| Point(rng.rand(w) as i64 - 25, rng.rand(w) as i64 - 25)).collect();
+        let edges = manhattan_mst_edges(&ps);
+        assert!(edges.len() <= 4 * n);
+        for e in &edges {
+            let d = (ps[e.u].0 - ps[e.v].0).abs() + (ps[e.u].1 - ps[e.v].1).abs();
+            assert_eq!(e.cost, d as u64);
+        }
+        let mut all = vec![];
+        for i in 0..n {
+            for j in i + 1..n {
+                let d = (ps[i].0 - ps[j].0).abs() + (ps[i].1 - ps[j].1).abs();
+                all.push(Edge { u: i, v: j, cost: d as u64 });
+            }
+        }
+        assert_eq!(manhattan_mst(&ps), kraskal(n, &mut all));
+    }
+}
+
+#[test]
+fn test_manhattan_farthest() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    let mut p = || Point(rng.rand(2001) as i64 - 1000, rng.rand(2001) as i64 - 1000);
+    let ps: Vec<Point<i64>> = (0..100).map(|_| p()).collect();
+    let mf = ManhattanFarthest::new(&ps);
+    let dist = |a: Point<i64>, b: Point<i64>| (a.0 - b.0).abs() + (a.1 - b.1).abs();
+    for _ in 0..100 {
+        let q = p();
+        assert_eq!(mf.query(q), ps.iter().map(|&a| dist(a, q)).max().unwrap());
+    }
+    let diam = ps.iter().flat_map(|&a| ps.iter().map(move |&b| dist(a, b))).max().unwrap();
+    assert_eq!(mf.diameter(), diam);
+}
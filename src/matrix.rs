@@ -1,24 +1,54 @@
 #[snippet = "Matrix"]
-#[derive(Clone)]
-pub struct Matrix {
-    v: Vec<Vec<i64>>,
+#[allow(dead_code)]
+pub trait Semiring {
+    type T: Clone;
+    fn zero() -> Self::T;
+    fn one() -> Self::T;
+    fn add(a: &Self::T, b: &Self::T) -> Self::T;
+    fn mul(a: &Self::T, b: &Self::T) -> Self::T;
 }
 #[snippet = "Matrix"]
-impl Matrix {
-    fn identity(n: usize) -> Self {
-        let mut v = vec![vec![0;n];n];
+#[allow(dead_code)]
+/// Semiring with subtraction and division, needed by Matrix_gauss
+pub trait Field: Semiring {
+    fn neg(a: &Self::T) -> Self::T;
+    fn inv(a: &Self::T) -> Self::T;
+    /// Used to choose the pivot. Must be 0.0 iff a is regarded as zero.
+    fn abs(a: &Self::T) -> f64;
+}
+#[snippet = "Matrix"]
+pub struct Matrix<S: Semiring> {
+    v: Vec<Vec<S::T>>,
+}
+#[snippet = "Matrix"]
+impl<S: Semiring> Clone for Matrix<S> {
+    fn clone(&self) -> Self {
+        Matrix { v: self.v.clone() }
+    }
+}
+#[snippet = "Matrix"]
+#[allow(dead_code)]
+impl<S: Semiring> Matrix<S> {
+    pub fn new(v: Vec<Vec<S::T>>) -> Self {
+        Matrix { v: v }
+    }
+    pub fn zero(m: usize, n: usize) -> Self {
+        Matrix { v: vec![vec![S::zero(); n]; m] }
+    }
+    pub fn identity(n: usize) -> Self {
+        let mut x = Self::zero(n, n);
         for i in 0..n {
-            v[i][i] = 1;
+            x.v[i][i] = S::one();
         }
-        Matrix { v: v }
+        x
     }
-    fn m(&self) -> usize {
+    pub fn m(&self) -> usize {
         self.v.len()
     }
-    fn n(&self) -> usize {
+    pub fn n(&self) -> usize {
         self.v[0].len()
     }
-    fn pow(&self, k: u64, m: i64) -> Self {
+    pub fn pow(&self, k: u64) -> Self {
         assert!(self.m() == self.n());
         let mut k = k;
         let mut x = Self::identity(self.m());
@@ -26,73 +56,208 @@ impl Matrix {
         while k > 0 {
             if k & 1 > 0 {
                 x = y.clone() * x;
-                x %= m;
             }
-            y = y.clone() * y.clone();
-            y %= m;
+            y = y.clone() * y;
             k >>= 1;
         }
         x
     }
 }
 #[snippet = "Matrix"]
-impl std::ops::Add for Matrix {
+impl<S: Semiring> std::ops::Index<usize> for Matrix<S> {
+    type Output = Vec<S::T>;
+    fn index(&self, i: usize) -> &Vec<S::T> {
+        &self.v[i]
+    }
+}
+#[snippet = "Matrix"]
+impl<S: Semiring> std::ops::IndexMut<usize> for Matrix<S> {
+    fn index_mut(&mut self, i: usize) -> &mut Vec<S::T> {
+        &mut self.v[i]
+    }
+}
+#[snippet = "Matrix"]
+impl<S: Semiring> std::ops::Add for Matrix<S> {
     type Output = Self;
     fn add(self, other: Self) -> Self {
-        let mut r = self.v.clone();
-        for i in 0..self.m() {
-            for j in 0..self.n() {
-                r[i][j] += other.v[i][j];
+        let mut r = self.v;
+        for i in 0..r.len() {
+            for j in 0..r[i].len() {
+                r[i][j] = S::add(&r[i][j], &other.v[i][j]);
             }
         }
         Matrix { v: r }
     }
 }
 #[snippet = "Matrix"]
-impl std::ops::Sub for Matrix {
+impl<S: Semiring> std::ops::Sub for Matrix<S> where S::T: std::ops::Sub<Output = S::T> {
     type Output = Self;
     fn sub(self, other: Self) -> Self {
-        let mut r = self.v.clone();
-        for i in 0..self.m() {
-            for j in 0..self.n() {
-                r[i][j] -= other.v[i][j];
+        let mut r = self.v;
+        for i in 0..r.len() {
+            for j in 0..r[i].len() {
+                r[i][j] = r[i][j].clone() - other.v[i][j].clone();
             }
         }
         Matrix { v: r }
     }
 }
 #[snippet = "Matrix"]
-impl std::ops::Mul for Matrix {
+impl<S: Semiring> std::ops::Mul for Matrix<S> {
     type Output = Self;
     fn mul(self, other: Self) -> Self {
-        let mut r = vec![vec![0; other.n()]; self.m()];
+        let mut r = vec![vec![S::zero(); other.n()]; self.m()];
         for i in 0..self.m() {
-            for j in 0..self.n() {
-                let mut v = 0;
-                for k in 0..self.n() {
-                    v += self.v[i][k] * other.v[k][j];
+            for k in 0..self.n() {
+                let a = &self.v[i][k];
+                for j in 0..other.n() {
+                    r[i][j] = S::add(&r[i][j], &S::mul(a, &other.v[k][j]));
                 }
-                r[i][j] = v;
             }
         }
         Matrix { v: r }
     }
 }
-#[snippet = "Matrix"]
-impl std::ops::Rem<i64> for Matrix {
-    type Output = Self;
-    fn rem(self, mo: i64) -> Self {
-        let mut r = self.v.clone();
-        for i in 0..self.m() {
-            for j in 0..self.n() {
-                r[i][j] %= mo;
+
+#[snippet = "Matrix_gauss"]
+#[allow(dead_code)]
+impl<S: Field> Matrix<S> {
+    #[doc = "reduce the first ncols columns to reduced row echelon form. returns (pivot columns, det factor). O(m^2 n)"]
+    fn gauss_jordan(&mut self, ncols: usize) -> (Vec<usize>, S::T) {
+        let m = self.m();
+        let n = self.n();
+        let mut det = S::one();
+        let mut pivots = vec![];
+        let mut r = 0;
+        for c in 0..ncols {
+            if r == m {
+                break;
+            }
+            let mut p = r;
+            for i in r + 1..m {
+                if S::abs(&self.v[i][c]) > S::abs(&self.v[p][c]) {
+                    p = i;
+                }
+            }
+            if S::abs(&self.v[p][c]) == 0.0 {
+                det = S::zero();
+                continue;
+            }
+            if p != r {
+                self.v.swap(p, r);
+                det = S::neg(&det);
+            }
+            det = S::mul(&det, &self.v[r][c]);
+            let inv = S::inv(&self.v[r][c]);
+            for j in c..n {
+                self.v[r][j] = S::mul(&self.v[r][j], &inv);
+            }
+            for i in 0..m {
+                if i == r || S::abs(&self.v[i][c]) == 0.0 {
+                    continue;
+                }
+                let k = S::neg(&self.v[i][c]);
+                for j in c..n {
+                    let d = S::mul(&k, &self.v[r][j]);
+                    self.v[i][j] = S::add(&self.v[i][j], &d);
+                }
             }
+            pivots.push(c);
+            r += 1;
         }
-        Matrix { v: r }
+        (pivots, det)
+    }
+    pub fn det(&self) -> S::T {
+        assert!(self.m() == self.n());
+        let mut a = self.clone();
+        let n = a.n();
+        let (pivots, det) = a.gauss_jordan(n);
+        if pivots.len() < n { S::zero() } else { det }
+    }
+    pub fn rank(&self) -> usize {
+        let mut a = self.clone();
+        let n = a.n();
+        a.gauss_jordan(n).0.len()
+    }
+    #[doc = "None if singular"]
+    pub fn inverse(&self) -> Option<Self> {
+        assert!(self.m() == self.n());
+        let n = self.n();
+        let mut a = Self::zero(n, 2 * n);
+        for i in 0..n {
+            for j in 0..n {
+                a.v[i][j] = self.v[i][j].clone();
+            }
+            a.v[i][n + i] = S::one();
+        }
+        if a.gauss_jordan(n).0.len() < n {
+            return None;
+        }
+        Some(Matrix { v: a.v.into_iter().map(|row| row[n..].to_vec()).collect() })
+    }
+    #[doc = "Ax=b. returns (x0, kernel basis) s.t. solutions are x0 + span(kernel), or None if inconsistent"]
+    pub fn solve(&self, b: &[S::T]) -> Option<(Vec<S::T>, Vec<Vec<S::T>>)> {
+        let m = self.m();
+        let n = self.n();
+        assert!(b.len() == m);
+        let mut a = Self::zero(m, n + 1);
+        for i in 0..m {
+            for j in 0..n {
+                a.v[i][j] = self.v[i][j].clone();
+            }
+            a.v[i][n] = b[i].clone();
+        }
+        let (pivots, _) = a.gauss_jordan(n);
+        let rank = pivots.len();
+        for i in rank..m {
+            if S::abs(&a.v[i][n]) > 0.0 {
+                return None;
+            }
+        }
+        let mut x = vec![S::zero(); n];
+        let mut is_pivot = vec![false; n];
+        for i in 0..rank {
+            x[pivots[i]] = a.v[i][n].clone();
+            is_pivot[pivots[i]] = true;
+        }
+        let mut kernel = vec![];
+        for f in 0..n {
+            if is_pivot[f] {
+                continue;
+            }
+            let mut y = vec![S::zero(); n];
+            y[f] = S::one();
+            for i in 0..rank {
+                y[pivots[i]] = S::neg(&a.v[i][f]);
+            }
+            kernel.push(y);
+        }
+        Some((x, kernel))
     }
 }
-#[snippet = "Matrix"]
-impl std::ops::RemAssign<i64> for Matrix {
+
+#[snippet = "Matrix_INT"]
+#[allow(dead_code)]
+/// plain i64 (wrapping). pow does not reduce; use MOD for modular powers
+pub struct INT;
+#[snippet = "Matrix_INT"]
+impl Semiring for INT {
+    type T = i64;
+    fn zero() -> i64 { 0 }
+    fn one() -> i64 { 1 }
+    fn add(a: &i64, b: &i64) -> i64 { a.wrapping_add(*b) }
+    fn mul(a: &i64, b: &i64) -> i64 { a.wrapping_mul(*b) }
+}
+#[snippet = "Matrix_INT"]
+impl std::ops::Rem<i64> for Matrix<INT> {
+    type Output = Self;
+    fn rem(mut self, mo: i64) -> Self {
+        self %= mo;
+        self
+    }
+}
+#[snippet = "Matrix_INT"]
+impl std::ops::RemAssign<i64> for Matrix<INT> {
     fn rem_assign(&mut self, mo: i64) {
         for i in 0..self.m() {
             for j in 0..self.n() {
@@ -102,58 +267,417 @@ impl std::ops::RemAssign<i64> for Matrix {
     }
 }
 
+#[snippet = "Matrix_INT"]
+#[allow(dead_code)]
+impl Matrix<INT> {
+    #[doc = "product reduced mod m. entries must be in [0, m) and m <= 3e9"]
+    pub fn mul_mod(&self, other: &Self, m: i64) -> Self {
+        let mut r = vec![vec![0; other.n()]; self.m()];
+        for i in 0..self.m() {
+            for k in 0..self.n() {
+                let a = self.v[i][k];
+                for j in 0..other.n() {
+                    r[i][j] = (r[i][j] + a * other.v[k][j] % m) % m;
+                }
+            }
+        }
+        Matrix { v: r }
+    }
+    #[doc = "self^k mod m for a modulus only known at runtime. O(n^3 log k)"]
+    pub fn pow_mod(&self, k: u64, m: i64) -> Self {
+        assert!(self.m() == self.n());
+        let mut k = k;
+        let mut x = Self::identity(self.m()) % m;
+        let mut y = self.clone() % m;
+        while k > 0 {
+            if k & 1 > 0 {
+                x = y.mul_mod(&x, m);
+            }
+            y = y.mul_mod(&y, m);
+            k >>= 1;
+        }
+        x
+    }
+}
+
+use crate::modint::Mod;
+#[snippet = "Matrix_MOD"]
+#[allow(dead_code)]
+pub struct MOD;
+#[snippet = "Matrix_MOD"]
+impl Semiring for MOD {
+    type T = Mod;
+    fn zero() -> Mod { Mod::new(0) }
+    fn one() -> Mod { Mod::new(1) }
+    fn add(a: &Mod, b: &Mod) -> Mod { *a + *b }
+    fn mul(a: &Mod, b: &Mod) -> Mod { *a * *b }
+}
+#[snippet = "Matrix_MOD"]
+impl Field for MOD {
+    fn neg(a: &Mod) -> Mod { -*a }
+    fn inv(a: &Mod) -> Mod { a.inv() }
+    fn abs(a: &Mod) -> f64 { if a.x == 0 { 0.0 } else { 1.0 } }
+}
+
+#[snippet = "Matrix_REAL"]
+#[allow(dead_code)]
+pub struct REAL;
+#[snippet = "Matrix_REAL"]
+impl Semiring for REAL {
+    type T = f64;
+    fn zero() -> f64 { 0.0 }
+    fn one() -> f64 { 1.0 }
+    fn add(a: &f64, b: &f64) -> f64 { a + b }
+    fn mul(a: &f64, b: &f64) -> f64 { a * b }
+}
+#[snippet = "Matrix_REAL"]
+impl Field for REAL {
+    fn neg(a: &f64) -> f64 { -a }
+    fn inv(a: &f64) -> f64 { 1.0 / a }
+    // partial pivoting picks the largest |a|
+    fn abs(a: &f64) -> f64 { if a.abs() < 1e-9 { 0.0 } else { a.abs() } }
+}
+
+#[snippet = "Matrix_BOOL"]
+#[allow(dead_code)]
+/// (or, and). pow(k)[i][j] = can we walk i -> j with exactly k edges
+pub struct BOOL;
+#[snippet = "Matrix_BOOL"]
+impl Semiring for BOOL {
+    type T = bool;
+    fn zero() -> bool { false }
+    fn one() -> bool { true }
+    fn add(a: &bool, b: &bool) -> bool { *a || *b }
+    fn mul(a: &bool, b: &bool) -> bool { *a && *b }
+}
+
+#[snippet = "Matrix_MAX_PLUS"]
+#[allow(dead_code)]
+/// (max, +). i64::MIN means no path. pow(k)[i][j] = longest walk with exactly k edges
+pub struct MAX_PLUS;
+#[snippet = "Matrix_MAX_PLUS"]
+impl Semiring for MAX_PLUS {
+    type T = i64;
+    fn zero() -> i64 { std::i64::MIN }
+    fn one() -> i64 { 0 }
+    fn add(a: &i64, b: &i64) -> i64 { std::cmp::max(*a, *b) }
+    fn mul(a: &i64, b: &i64) -> i64 {
+        if *a == std::i64::MIN || *b == std::i64::MIN { std::i64::MIN } else { a + b }
+    }
+}
+
+#[snippet = "Matrix_MIN_PLUS"]
+#[allow(dead_code)]
+/// (min, +). i64::MAX means no path. pow(k)[i][j] = shortest walk with exactly k edges
+pub struct MIN_PLUS;
+#[snippet = "Matrix_MIN_PLUS"]
+impl Semiring for MIN_PLUS {
+    type T = i64;
+    fn zero() -> i64 { std::i64::MAX }
+    fn one() -> i64 { 0 }
+    fn add(a: &i64, b: &i64) -> i64 { std::cmp::min(*a, *b) }
+    fn mul(a: &i64, b: &i64) -> i64 {
+        if *a == std::i64::MAX || *b == std::i64::MAX { std::i64::MAX } else { a + b }
+    }
+}
+
+#[snippet = "SmallMatrix"]
+#[allow(unused_macros)]
+#[doc = "array backed n x n matrix over Z/mZ. products are summed before `%` so n * (m-1)^2 must fit in u64 (n <= 18 for m = 1e9+7)"]
+macro_rules! define_small_matrix {
+    ($name: ident, $n: expr, $modulo: expr) => {
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub struct $name {
+            pub v: [[u64; $n]; $n],
+        }
+        #[allow(dead_code)]
+        impl $name {
+            pub fn zero() -> Self {
+                $name { v: [[0; $n]; $n] }
+            }
+            pub fn identity() -> Self {
+                let mut x = Self::zero();
+                for i in 0..$n {
+                    x.v[i][i] = 1;
+                }
+                x
+            }
+            pub fn pow(self, k: u64) -> Self {
+                let mut k = k;
+                let mut x = Self::identity();
+                let mut y = self;
+                while k > 0 {
+                    if k & 1 > 0 {
+                        x = y * x;
+                    }
+                    y = y * y;
+                    k >>= 1;
+                }
+                x
+            }
+            pub fn mul_vec(&self, a: &[u64; $n]) -> [u64; $n] {
+                let mut r = [0; $n];
+                for i in 0..$n {
+                    let mut s = 0;
+                    for k in 0..$n {
+                        s += self.v[i][k] * a[k];
+                    }
+                    r[i] = s % $modulo;
+                }
+                r
+            }
+        }
+        impl std::ops::Mul for $name {
+            type Output = Self;
+            fn mul(self, other: Self) -> Self {
+                let mut r = [[0; $n]; $n];
+                for i in 0..$n {
+                    for j in 0..$n {
+                        let mut s = 0;
+                        for k in 0..$n {
+                            s += self.v[i][k] * other.v[k][j];
+                        }
+                        r[i][j] = s % $modulo;
+                    }
+                }
+                $name { v: r }
+            }
+        }
+    };
+}
+
 #[test]
 fn test_matrix_add() {
-    let a = Matrix {
+    let a: Matrix<INT> = Matrix {
         v: vec![vec![1,2],vec![3,4]]
     };
-    let b = Matrix {
+    let b: Matrix<INT> = Matrix {
         v: vec![vec![5,6],vec![7,8]]
     };
     let c = a.clone()+b.clone();
-    dbg!(c.v);
+    assert_eq!(c.v, vec![vec![6,8],vec![10,12]]);
     let d = a-b;
-    dbg!(d.v);
+    assert_eq!(d.v, vec![vec![-4,-4],vec![-4,-4]]);
 }
 #[test]
 fn test_matrix_sub() {
-    let a = Matrix {
+    let a: Matrix<INT> = Matrix {
         v: vec![vec![1,2],vec![3,4]]
     };
-    let b = Matrix {
+    let b: Matrix<INT> = Matrix {
         v: vec![vec![5,6],vec![7,8]]
     };
     let c = a-b;
-    dbg!(c.v);
-
+    assert_eq!(c.v, vec![vec![-4,-4],vec![-4,-4]]);
 }
 #[test]
 fn test_matrix_rem() {
-    let mut a = Matrix {
+    let mut a: Matrix<INT> = Matrix {
         v: vec![vec![5,6],vec![7,8]]
     };
     a%=3;
-    dbg!(a.v);
+    assert_eq!(a.v, vec![vec![2,0],vec![1,2]]);
 }
 #[test]
 fn test_matrix_mul() {
-    let a = Matrix {
+    let a: Matrix<INT> = Matrix {
         v: vec![vec![1,2],vec![3,4]]
     };
-    let b = Matrix {
+    let b: Matrix<INT> = Matrix {
         v: vec![vec![5,6],vec![7,8]]
     };
     let c = a*b;
-    dbg!(c.v);
+    assert_eq!(c.v, vec![vec![19,22],vec![43,50]]);
 }
 #[test]
 fn test_matrix_pow() {
-    let x = Matrix {
+    let x: Matrix<INT> = Matrix {
         v: vec![
             vec![1,2],
             vec![3,4]
         ]
     };
     let x3 = x.clone() * x.clone() * x.clone();
-    assert_eq!(x.pow(3, std::i64::MAX).v, x3.v);
-}
\ No newline at end of file
+    assert_eq!(x.pow(3).v, x3.v);
+    assert_eq!(x.pow_mod(3, 10).v, (x3 % 10).v);
+    // fib(90) = 2880067194370816120 fits in i64
+    let f: Matrix<INT> = Matrix::new(vec![vec![1, 1], vec![1, 0]]);
+    assert_eq!(f.pow_mod(90, 998244353)[0][1], 2880067194370816120 % 998244353);
+    assert_eq!(f.pow_mod(0, 7).v, vec![vec![1, 0], vec![0, 1]]);
+}
+#[test]
+fn test_matrix_pow_mod() {
+    // fib(n) mod 1e9+7
+    let x: Matrix<MOD> = Matrix::new(vec![
+        vec![1.into(), 1.into()],
+        vec![1.into(), 0.into()],
+    ]);
+    let mut a: Mod = 0.into();
+    let mut b: Mod = 1.into();
+    for n in 0..200 {
+        assert_eq!(x.pow(n)[1][0], a);
+        let c = a + b;
+        a = b;
+        b = c;
+    }
+}
+#[test]
+fn test_matrix_pow_bool() {
+    // 0 -> 1 -> 2 -> 0, 2 -> 3
+    let mut x: Matrix<BOOL> = Matrix::zero(4, 4);
+    x[0][1] = true;
+    x[1][2] = true;
+    x[2][0] = true;
+    x[2][3] = true;
+    assert_eq!(x.pow(2)[0][3], false);
+    assert_eq!(x.pow(3)[0][3], true);
+    assert_eq!(x.pow(5)[0][3], false);
+    assert_eq!(x.pow(6)[0][3], true);
+    assert_eq!(x.pow(100)[3][0], false);
+}
+#[test]
+fn test_matrix_pow_min_max_plus() {
+    let inf = std::i64::MAX;
+    let g = vec![
+        vec![inf, 1, 5],
+        vec![inf, inf, 1],
+        vec![2, inf, inf],
+    ];
+    let x: Matrix<MIN_PLUS> = Matrix::new(g.clone());
+    assert_eq!(x.pow(2)[0][2], 2);
+    assert_eq!(x.pow(3)[0][0], 4);
+    assert_eq!(x.pow(4)[0][2], 9);
+    assert_eq!(x.pow(1)[1][0], inf);
+
+    let y: Matrix<MAX_PLUS> = Matrix::new(g.iter().map(|r| {
+        r.iter().map(|&c| if c == inf { std::i64::MIN } else { c }).collect()
+    }).collect());
+    assert_eq!(y.pow(2)[2][1], 3);
+    assert_eq!(y.pow(3)[0][0], 4);
+    assert_eq!(y.pow(4)[0][1], 5);
+    assert_eq!(y.pow(1)[0][0], std::i64::MIN);
+
+    let z: Matrix<REAL> = Matrix::new(vec![vec![0.5, 0.5], vec![0.0, 1.0]]);
+    assert!((z.pow(10)[0][1] - (1.0 - 0.5f64.powi(10))).abs() < 1e-12);
+}
+#[test]
+fn test_matrix_gauss_mod() {
+    let a: Matrix<MOD> = Matrix::new(vec![
+        vec![2.into(), 1.into(), 1.into()],
+        vec![1.into(), 3.into(), 2.into()],
+        vec![1.into(), 0.into(), 0.into()],
+    ]);
+    assert_eq!(a.det(), Mod::new(0) - 1);
+    assert_eq!(a.rank(), 3);
+    let ai = a.inverse().unwrap();
+    let e = a.clone() * ai.clone();
+    for i in 0..3 {
+        for j in 0..3 {
+            assert_eq!(e[i][j], if i == j { 1.into() } else { 0.into() });
+        }
+    }
+    let b: Vec<Mod> = vec![4.into(), 5.into(), 6.into()];
+    let (x, kernel) = a.solve(&b).unwrap();
+    assert!(kernel.is_empty());
+    for i in 0..3 {
+        let mut s: Mod = 0.into();
+        for j in 0..3 {
+            s += a[i][j] * x[j];
+        }
+        assert_eq!(s, b[i]);
+    }
+
+    // rank 1
+    let s: Matrix<MOD> = Matrix::new(vec![
+        vec![1.into(), 2.into(), 3.into()],
+        vec![2.into(), 4.into(), 6.into()],
+    ]);
+    assert_eq!(s.rank(), 1);
+    assert!(s.solve(&[1.into(), 3.into()]).is_none());
+    let (x, kernel) = s.solve(&[1.into(), 2.into()]).unwrap();
+    assert_eq!(kernel.len(), 2);
+    for y in kernel.iter().chain(std::iter::once(&x)) {
+        let mut t: Mod = 0.into();
+        for j in 0..3 {
+            t += s[0][j] * y[j];
+        }
+        assert_eq!(t, if y == &x { 1.into() } else { 0.into() });
+    }
+}
+#[test]
+fn test_matrix_gauss_real() {
+    let a: Matrix<REAL> = Matrix::new(vec![
+        vec![1e-12, 1.0],
+        vec![1.0, 1.0],
+    ]);
+    assert!((a.det() + 1.0).abs() < 1e-9);
+    let (x, kernel) = a.solve(&[1.0, 2.0]).unwrap();
+    assert!(kernel.is_empty());
+    assert!((x[0] - 1.0).abs() < 1e-9);
+    assert!((x[1] - 1.0).abs() < 1e-9);
+
+    let s: Matrix<REAL> = Matrix::new(vec![vec![1.0, 2.0], vec![0.5, 1.0]]);
+    assert_eq!(s.det(), 0.0);
+    assert!(s.inverse().is_none());
+    let (_, kernel) = s.solve(&[2.0, 1.0]).unwrap();
+    assert_eq!(kernel.len(), 1);
+    assert!((kernel[0][0] + 2.0 * kernel[0][1]).abs() < 1e-9);
+}
+#[test]
+fn test_small_matrix_pow() {
+    define_small_matrix!(M3, 3, 1_000_000_007);
+    // tribonacci
+    let x = M3 { v: [[1, 1, 1], [1, 0, 0], [0, 1, 0]] };
+    let y: Matrix<MOD> = Matrix::new(vec![
+        vec![1.into(), 1.into(), 1.into()],
+        vec![1.into(), 0.into(), 0.into()],
+        vec![0.into(), 1.into(), 0.into()],
+    ]);
+    for &k in &[0, 1, 2, 10, 1000, 123_456_789_012_345] {
+        let a = x.pow(k);
+        let b = y.pow(k);
+        for i in 0..3 {
+            for j in 0..3 {
+                assert_eq!(a.v[i][j] as i64, b[i][j].x);
+            }
+        }
+    }
+    assert_eq!(x.pow(4).mul_vec(&[1, 0, 0]), [7, 4, 2]);
+}
+
+#[cfg(test)]
+fn mk_fib_queries(n: usize) -> Vec<u64> {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    (0..n).map(|_| rng.rand(1_000_000_000_000_000_000)).collect()
+}
+
+#[bench]
+fn bench_matrix_pow_fib(b: &mut test::Bencher) {
+    let qs = mk_fib_queries(100_000);
+    let x: Matrix<MOD> = Matrix::new(vec![
+        vec![1.into(), 1.into()],
+        vec![1.into(), 0.into()],
+    ]);
+    b.iter(|| {
+        let mut acc: Mod = 0.into();
+        for &k in &qs {
+            acc += x.pow(k)[1][0];
+        }
+        acc
+    })
+}
+
+#[bench]
+fn bench_small_matrix_pow_fib(b: &mut test::Bencher) {
+    define_small_matrix!(M2, 2, 1_000_000_007);
+    let qs = mk_fib_queries(100_000);
+    let x = M2 { v: [[1, 1], [1, 0]] };
+    b.iter(|| {
+        let mut acc = 0;
+        for &k in &qs {
+            acc += x.pow(k).v[1][0];
+        }
+        acc
+    })
+}
@@ -90,4 +90,195 @@ fn test_lexical_permutation() {
             break;
         }
     }
-}
\ No newline at end of file
+}
+
+use crate::fenwick::BIT;
+
+#[snippet = "Permutation"]
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Permutation of 0..n. p[i] is the image of i.
+pub struct Permutation {
+    p: Vec<usize>,
+}
+
+#[snippet = "Permutation"]
+#[allow(dead_code)]
+impl Permutation {
+    pub fn new(p: Vec<usize>) -> Permutation {
+        let mut used = vec![false; p.len()];
+        for &x in &p {
+            assert!(x < p.len() && !used[x]);
+            used[x] = true;
+        }
+        Permutation { p: p }
+    }
+    pub fn identity(n: usize) -> Permutation {
+        Permutation { p: (0..n).collect() }
+    }
+    pub fn len(&self) -> usize {
+        self.p.len()
+    }
+    pub fn as_slice(&self) -> &[usize] {
+        &self.p
+    }
+    #[doc = "c[i] = #{j > i | p[j] < p[i]}. O(n log n)"]
+    pub fn lehmer_code(&self) -> Vec<usize> {
+        let n = self.len();
+        let mut bit = BIT::new(n);
+        let mut c = vec![0; n];
+        for i in (0..n).rev() {
+            c[i] = bit.sum(self.p[i]);
+            bit.add(self.p[i] + 1, 1);
+        }
+        c
+    }
+    #[doc = "inverse of lehmer_code. O(n log n)"]
+    pub fn from_lehmer_code(c: &[usize]) -> Permutation {
+        let n = c.len();
+        let mut bit = BIT::new(n);
+        for i in 1..n + 1 {
+            bit.add(i, 1);
+        }
+        let mut p = vec![0; n];
+        for i in 0..n {
+            assert!(c[i] < n - i);
+            let k = bit.lower_bound(c[i] as i64 + 1);
+            bit.add(k, -1);
+            p[i] = k - 1;
+        }
+        Permutation { p: p }
+    }
+    #[doc = "0-indexed lexicographic rank. n <= 20. O(n log n)"]
+    pub fn rank(&self) -> u64 {
+        let n = self.len();
+        let c = self.lehmer_code();
+        let mut r = 0;
+        for i in 0..n {
+            r = r * (n - i) as u64 + c[i] as u64;
+        }
+        r
+    }
+    #[doc = "k-th (0-indexed) permutation of 0..n in lexicographic order. n <= 20. O(n log n)"]
+    pub fn unrank(n: usize, k: u64) -> Permutation {
+        let mut c = vec![0; n];
+        let mut k = k;
+        for i in (0..n).rev() {
+            let base = (n - i) as u64;
+            c[i] = (k % base) as usize;
+            k /= base;
+        }
+        assert!(k == 0);
+        Self::from_lehmer_code(&c)
+    }
+    #[doc = "cycles including fixed points. each cycle starts at its minimum element. O(n)"]
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let n = self.len();
+        let mut used = vec![false; n];
+        let mut res = vec![];
+        for s in 0..n {
+            if used[s] {
+                continue;
+            }
+            let mut cycle = vec![];
+            let mut v = s;
+            while !used[v] {
+                used[v] = true;
+                cycle.push(v);
+                v = self.p[v];
+            }
+            res.push(cycle);
+        }
+        res
+    }
+    pub fn inverse(&self) -> Permutation {
+        let mut q = vec![0; self.len()];
+        for i in 0..self.len() {
+            q[self.p[i]] = i;
+        }
+        Permutation { p: q }
+    }
+    #[doc = "(self . other)(i) = self(other(i))"]
+    pub fn compose(&self, other: &Permutation) -> Permutation {
+        assert!(self.len() == other.len());
+        Permutation {
+            p: other.p.iter().map(|&i| self.p[i]).collect(),
+        }
+    }
+    #[doc = "self^k via cycle decomposition. O(n)"]
+    pub fn pow(&self, k: u64) -> Permutation {
+        let mut q = vec![0; self.len()];
+        for cycle in self.cycles() {
+            let l = cycle.len();
+            let d = (k % l as u64) as usize;
+            for i in 0..l {
+                q[cycle[i]] = cycle[(i + d) % l];
+            }
+        }
+        Permutation { p: q }
+    }
+    #[doc = "0 if even, 1 if odd"]
+    pub fn parity(&self) -> usize {
+        (self.len() - self.cycles().len()) % 2
+    }
+}
+
+#[snippet = "Permutation"]
+impl std::ops::Index<usize> for Permutation {
+    type Output = usize;
+    fn index(&self, i: usize) -> &usize {
+        &self.p[i]
+    }
+}
+
+#[snippet = "Permutation"]
+impl std::ops::Mul for Permutation {
+    type Output = Permutation;
+    fn mul(self, other: Permutation) -> Permutation {
+        self.compose(&other)
+    }
+}
+
+#[test]
+fn test_permutation_rank() {
+    let mut x = vec![0, 1, 2, 3, 4];
+    let mut k = 0;
+    loop {
+        let p = Permutation::new(x.clone());
+        assert_eq!(p.rank(), k);
+        assert_eq!(Permutation::unrank(5, k), p);
+        k += 1;
+        if !x.next_permutation() {
+            break;
+        }
+    }
+    assert_eq!(k, 120);
+}
+
+#[test]
+fn test_permutation_cycles() {
+    let p = Permutation::new(vec![1, 2, 0, 4, 3, 5]);
+    assert_eq!(p.cycles(), vec![vec![0, 1, 2], vec![3, 4], vec![5]]);
+    assert_eq!(p.parity(), 1);
+    assert_eq!(p.inverse().as_slice(), &[2, 0, 1, 4, 3, 5]);
+    assert_eq!(p.compose(&p.inverse()), Permutation::identity(6));
+    assert_eq!(p.pow(6), Permutation::identity(6));
+}
+
+#[test]
+fn test_permutation_pow() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    let n = 30;
+    let mut x: Vec<usize> = (0..n).collect();
+    for i in (1..n).rev() {
+        let j = rng.rand(i as u64 + 1) as usize;
+        x.swap(i, j);
+    }
+    let p = Permutation::new(x);
+    let mut q = Permutation::identity(n);
+    for k in 0..50 {
+        assert_eq!(p.pow(k), q);
+        assert_eq!(p.pow(k).parity(), (k as usize * p.parity()) % 2);
+        q = p.clone() * q;
+    }
+}
@@ -0,0 +1,225 @@
+use crate::seg::Monoid;
+
+// node 0 is the null node whose children are itself, so an empty version is root 0.
+
+#[snippet = "PersistentSEG"]
+#[allow(dead_code)]
+/// Persistent segment tree. Every update returns the root of a new version; old roots stay valid.
+pub struct PersistentSEG<M: Monoid> {
+    log: usize,
+    buf: Vec<M::T>,
+    ch: Vec<[usize; 2]>,
+}
+
+#[snippet = "PersistentSEG"]
+#[allow(dead_code)]
+impl<M: Monoid> PersistentSEG<M> {
+    #[doc = "version 0 holds id at every index in [0, n)"]
+    pub fn new(n: usize) -> PersistentSEG<M> {
+        let mut log = 0;
+        while (1 << log) < n {
+            log += 1;
+        }
+        PersistentSEG {
+            log: log,
+            buf: vec![M::id()],
+            ch: vec![[0, 0]],
+        }
+    }
+    #[doc = "number of allocated nodes"]
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+    fn alloc(&mut self, x: M::T, ch: [usize; 2]) -> usize {
+        self.buf.push(x);
+        self.ch.push(ch);
+        self.buf.len() - 1
+    }
+    fn do_build(&mut self, d: usize, l: usize, xs: &[M::T]) -> usize {
+        if l >= xs.len() {
+            return 0;
+        }
+        if d == 0 {
+            return self.alloc(xs[l].clone(), [0, 0]);
+        }
+        let c0 = self.do_build(d - 1, l, xs);
+        let c1 = self.do_build(d - 1, l + (1 << (d - 1)), xs);
+        let x = M::op(&self.buf[c0], &self.buf[c1]);
+        self.alloc(x, [c0, c1])
+    }
+    #[doc = "root of a new version holding xs. O(n)"]
+    pub fn build(&mut self, xs: &[M::T]) -> usize {
+        let log = self.log;
+        self.do_build(log, 0, xs)
+    }
+    fn do_update(&mut self, v: usize, d: usize, k: usize, x: M::T) -> usize {
+        if d == 0 {
+            return self.alloc(x, [0, 0]);
+        }
+        let mut c = self.ch[v];
+        let b = k >> (d - 1) & 1;
+        c[b] = self.do_update(c[b], d - 1, k, x);
+        let y = M::op(&self.buf[c[0]], &self.buf[c[1]]);
+        self.alloc(y, c)
+    }
+    #[doc = "root of the version made from root by setting x at k. O(log n) time and new nodes"]
+    pub fn update(&mut self, root: usize, k: usize, x: M::T) -> usize {
+        let log = self.log;
+        self.do_update(root, log, k, x)
+    }
+    pub fn get(&self, root: usize, k: usize) -> M::T {
+        let mut v = root;
+        for d in (0..self.log).rev() {
+            v = self.ch[v][k >> d & 1];
+        }
+        self.buf[v].clone()
+    }
+    fn do_query(&self, v: usize, d: usize, l: usize, a: usize, b: usize) -> M::T {
+        let r = l + (1 << d);
+        if v == 0 || r <= a || b <= l {
+            return M::id();
+        }
+        if a <= l && r <= b {
+            return self.buf[v].clone();
+        }
+        let m = l + (1 << (d - 1));
+        let [c0, c1] = self.ch[v];
+        M::op(&self.do_query(c0, d - 1, l, a, b), &self.do_query(c1, d - 1, m, a, b))
+    }
+    #[doc = "[a, b) of the version root. O(log n)"]
+    pub fn query(&self, root: usize, a: usize, b: usize) -> M::T {
+        if a >= b {
+            return M::id();
+        }
+        self.do_query(root, self.log, 0, a, b)
+    }
+}
+
+#[snippet = "PersistentKth"]
+struct CNT;
+#[snippet = "PersistentKth"]
+impl Monoid for CNT {
+    type T = usize;
+    fn id() -> usize {
+        0
+    }
+    fn op(a: &usize, b: &usize) -> usize {
+        a + b
+    }
+}
+
+#[snippet = "PersistentKth"]
+#[allow(dead_code)]
+/// Order statistics on subarrays. Version i counts the values of xs[0, i).
+pub struct PersistentKth {
+    vals: Vec<i64>,
+    roots: Vec<usize>,
+    seg: PersistentSEG<CNT>,
+}
+
+#[snippet = "PersistentKth"]
+#[allow(dead_code)]
+impl PersistentKth {
+    #[doc = "O(n log n)"]
+    pub fn new(xs: &[i64]) -> PersistentKth {
+        let mut vals = xs.to_vec();
+        vals.sort();
+        vals.dedup();
+        let mut seg = PersistentSEG::new(vals.len());
+        let mut roots = vec![0];
+        for &x in xs {
+            let i = vals.binary_search(&x).unwrap();
+            let root = roots[roots.len() - 1];
+            let c = seg.get(root, i);
+            roots.push(seg.update(root, i, c + 1));
+        }
+        PersistentKth {
+            vals: vals,
+            roots: roots,
+            seg: seg,
+        }
+    }
+    #[doc = "k-th (0-indexed) smallest value in xs[l, r). k < r - l. O(log n)"]
+    pub fn kth_smallest(&self, l: usize, r: usize, k: usize) -> i64 {
+        let (mut u, mut v) = (self.roots[l], self.roots[r]);
+        let mut k = k;
+        let mut i = 0;
+        for d in (0..self.seg.log).rev() {
+            let cnt = self.seg.buf[self.seg.ch[v][0]] - self.seg.buf[self.seg.ch[u][0]];
+            let b = if k < cnt {
+                0
+            } else {
+                k -= cnt;
+                1
+            };
+            u = self.seg.ch[u][b];
+            v = self.seg.ch[v][b];
+            i |= b << d;
+        }
+        self.vals[i]
+    }
+    #[doc = "number of values less than x in xs[l, r). O(log n)"]
+    pub fn count_less(&self, l: usize, r: usize, x: i64) -> usize {
+        let i = match self.vals.binary_search(&x) {
+            Ok(i) | Err(i) => i,
+        };
+        self.seg.query(self.roots[r], 0, i) - self.seg.query(self.roots[l], 0, i)
+    }
+}
+
+#[test]
+fn test_persistent_seg() {
+    use crate::seg::SUM;
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    for n in 1..40 {
+        let init: Vec<i64> = (0..n).map(|_| rng.rand(100) as i64).collect();
+        let mut seg: PersistentSEG<SUM> = PersistentSEG::new(n);
+        let mut roots = vec![seg.build(&init)];
+        let mut hist = vec![init];
+        for _ in 0..100 {
+            let ver = rng.rand(roots.len() as u64) as usize;
+            let k = rng.rand(n as u64) as usize;
+            let x = rng.rand(100) as i64;
+            roots.push(seg.update(roots[ver], k, x));
+            let mut v = hist[ver].clone();
+            v[k] = x;
+            hist.push(v);
+
+            let ver = rng.rand(roots.len() as u64) as usize;
+            let a = rng.rand(n as u64 + 1) as usize;
+            let b = rng.rand(n as u64 + 1) as usize;
+            let (a, b) = (a.min(b), a.max(b));
+            assert_eq!(seg.query(roots[ver], a, b), hist[ver][a..b].iter().sum::<i64>());
+            assert_eq!(seg.get(roots[ver], k), hist[ver][k]);
+        }
+    }
+    let mut seg: PersistentSEG<SUM> = PersistentSEG::new(5);
+    let r1 = seg.update(0, 3, 7);
+    assert_eq!(seg.query(0, 0, 5), 0);
+    assert_eq!(seg.query(r1, 0, 5), 7);
+}
+
+#[test]
+fn test_persistent_kth() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    for _ in 0..50 {
+        let n = rng.rand(100) as usize + 1;
+        let xs: Vec<i64> = (0..n).map(|_| rng.rand(50) as i64 - 25).collect();
+        let pk = PersistentKth::new(&xs);
+        for _ in 0..100 {
+            let a = rng.rand(n as u64 + 1) as usize;
+            let b = rng.rand(n as u64 + 1) as usize;
+            let (l, r) = (a.min(b), a.max(b));
+            let x = rng.rand(60) as i64 - 30;
+            assert_eq!(pk.count_less(l, r, x), xs[l..r].iter().filter(|&&y| y < x).count());
+            if l < r {
+                let mut v = xs[l..r].to_vec();
+                v.sort();
+                let k = rng.rand((r - l) as u64) as usize;
+                assert_eq!(pk.kth_smallest(l, r, k), v[k]);
+            }
+        }
+    }
+}
@@ -10,6 +10,7 @@ pub trait Monoid {
 #[allow(dead_code)]
 pub struct SEG<M: Monoid> {
     n: usize,
+    size: usize,
     buf: Vec<M::T>,
 }
 
@@ -21,6 +22,7 @@ impl<M: Monoid> SEG<M> {
         while m < n { m *= 2; }
         SEG {
             n: m,
+            size: n,
             buf: vec![M::id().clone(); 2 * m],
         }
     }
@@ -41,30 +43,96 @@ impl<M: Monoid> SEG<M> {
         self.buf[k + self.n].clone()
     }
 
-    pub fn do_query(&self, a: usize, b: usize, k: usize, l: usize, r: usize) -> M::T {
-        if r <= a || b <= l {
-            return M::id();
+    #[allow(dead_code)]
+    // [a,b)
+    pub fn query(&self, a: usize, b: usize) -> M::T {
+        let mut sl = M::id();
+        let mut sr = M::id();
+        let mut a = a + self.n;
+        let mut b = b + self.n;
+        while a < b {
+            if a & 1 == 1 {
+                sl = M::op(&sl, &self.buf[a]);
+                a += 1;
+            }
+            if b & 1 == 1 {
+                b -= 1;
+                sr = M::op(&self.buf[b], &sr);
+            }
+            a >>= 1;
+            b >>= 1;
         }
+        M::op(&sl, &sr)
+    }
 
-        if a <= l && r <= b {
-            return self.buf[k].clone();
-        } else {
-            let vl = self.do_query(a,b,k*2,l,(l+r)/2);
-            let vr = self.do_query(a,b,k*2+1,(l+r)/2,r);
-            return M::op(&vl, &vr);
+    #[allow(dead_code)]
+    #[doc = "max r such that pred(query(l, r)). pred(id) must hold and pred must be monotone. O(log n)"]
+    pub fn max_right<F: Fn(&M::T) -> bool>(&self, l: usize, pred: F) -> usize {
+        assert!(pred(&M::id()));
+        if l == self.size {
+            return self.size;
+        }
+        let mut l = l + self.n;
+        let mut sm = M::id();
+        loop {
+            while l % 2 == 0 {
+                l >>= 1;
+            }
+            if !pred(&M::op(&sm, &self.buf[l])) {
+                while l < self.n {
+                    l *= 2;
+                    let t = M::op(&sm, &self.buf[l]);
+                    if pred(&t) {
+                        sm = t;
+                        l += 1;
+                    }
+                }
+                return l - self.n;
+            }
+            sm = M::op(&sm, &self.buf[l]);
+            l += 1;
+            if l & l.wrapping_neg() == l {
+                return self.size;
+            }
         }
     }
 
     #[allow(dead_code)]
-    // [a,b)
-    pub fn query(&self, a: usize, b: usize) -> M::T {
-        self.do_query(a,b,1,0,self.n)
+    #[doc = "min l such that pred(query(l, r)). pred(id) must hold and pred must be monotone. O(log n)"]
+    pub fn min_left<F: Fn(&M::T) -> bool>(&self, r: usize, pred: F) -> usize {
+        assert!(pred(&M::id()));
+        if r == 0 {
+            return 0;
+        }
+        let mut r = r + self.n;
+        let mut sm = M::id();
+        loop {
+            r -= 1;
+            while r > 1 && r % 2 == 1 {
+                r >>= 1;
+            }
+            if !pred(&M::op(&self.buf[r], &sm)) {
+                while r < self.n {
+                    r = 2 * r + 1;
+                    let t = M::op(&self.buf[r], &sm);
+                    if pred(&t) {
+                        sm = t;
+                        r -= 1;
+                    }
+                }
+                return r + 1 - self.n;
+            }
+            sm = M::op(&self.buf[r], &sm);
+            if r & r.wrapping_neg() == r {
+                return 0;
+            }
+        }
     }
 }
 
 #[snippet = "SEG_SUM"]
 #[allow(dead_code)]
-struct SUM;
+pub(crate) struct SUM;
 #[snippet = "SEG_SUM"]
 impl Monoid for SUM {
     type T = i64;
@@ -115,4 +183,169 @@ impl Monoid for MAX {
     fn op(a: &Self::T, b: &Self::T) -> Self::T {
         std::cmp::max(*a, *b)
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_seg_max_right_min_left() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    for _ in 0..50 {
+        let n = rng.rand(40) as usize + 1;
+        let mut seg: SEG<SUM> = SEG::new(n);
+        let mut mx: SEG<MAX> = SEG::new(n);
+        let mut v = vec![0; n];
+        for i in 0..n {
+            v[i] = rng.rand(10) as i64;
+            seg.update(i, v[i]);
+            mx.update(i, v[i]);
+        }
+        for _ in 0..100 {
+            let a = rng.rand(n as u64 + 1) as usize;
+            let b = rng.rand(n as u64 + 1) as usize;
+            let (a, b) = (a.min(b), a.max(b));
+            assert_eq!(seg.query(a, b), v[a..b].iter().sum::<i64>());
+            let w = rng.rand(60) as i64;
+            let r = (a..=n).filter(|&r| v[a..r].iter().sum::<i64>() <= w).max().unwrap();
+            assert_eq!(seg.max_right(a, |&s| s <= w), r);
+            let l = (0..=b).filter(|&l| v[l..b].iter().sum::<i64>() <= w).min().unwrap();
+            assert_eq!(seg.min_left(b, |&s| s <= w), l);
+            let r = (a..=n).filter(|&r| v[a..r].iter().all(|&x| x < w)).max().unwrap();
+            assert_eq!(mx.max_right(a, |&m| m < w), r);
+            let l = (0..=b).filter(|&l| v[l..b].iter().all(|&x| x < w)).min().unwrap();
+            assert_eq!(mx.min_left(b, |&m| m < w), l);
+        }
+    }
+}
+
+#[snippet = "SEG2D"]
+#[allow(dead_code)]
+/// 2D segment tree on an h x w grid. op must be commutative. O(hw) memory
+pub struct SEG2D<M: Monoid> {
+    h: usize,
+    w: usize,
+    buf: Vec<M::T>,
+}
+
+#[snippet = "SEG2D"]
+#[allow(dead_code)]
+impl<M: Monoid> SEG2D<M> {
+    pub fn new(h: usize, w: usize) -> SEG2D<M> {
+        let mut hh = 1;
+        while hh < h { hh *= 2; }
+        let mut ww = 1;
+        while ww < w { ww *= 2; }
+        SEG2D {
+            h: hh,
+            w: ww,
+            buf: vec![M::id(); 4 * hh * ww],
+        }
+    }
+
+    fn idx(&self, i: usize, j: usize) -> usize {
+        i * 2 * self.w + j
+    }
+
+    #[doc = "O(log h log w)"]
+    pub fn update(&mut self, i: usize, j: usize, a: M::T) {
+        let mut i = i + self.h;
+        let j = j + self.w;
+        let k = self.idx(i, j);
+        self.buf[k] = a;
+        loop {
+            let mut j = j;
+            if i >= self.h {
+                j >>= 1;
+            }
+            // leaf rows only need the column ancestors, inner rows need the cell itself too
+            while j > 0 {
+                let k = self.idx(i, j);
+                self.buf[k] = if i >= self.h {
+                    M::op(&self.buf[self.idx(i, 2 * j)], &self.buf[self.idx(i, 2 * j + 1)])
+                } else {
+                    M::op(&self.buf[self.idx(2 * i, j)], &self.buf[self.idx(2 * i + 1, j)])
+                };
+                j >>= 1;
+            }
+            if i == 1 {
+                break;
+            }
+            i >>= 1;
+        }
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> M::T {
+        self.buf[self.idx(i + self.h, j + self.w)].clone()
+    }
+
+    fn query_row(&self, i: usize, a: usize, b: usize) -> M::T {
+        let mut s = M::id();
+        let mut a = a + self.w;
+        let mut b = b + self.w;
+        while a < b {
+            if a & 1 == 1 {
+                s = M::op(&s, &self.buf[self.idx(i, a)]);
+                a += 1;
+            }
+            if b & 1 == 1 {
+                b -= 1;
+                s = M::op(&s, &self.buf[self.idx(i, b)]);
+            }
+            a >>= 1;
+            b >>= 1;
+        }
+        s
+    }
+
+    #[doc = "[i0,i1) x [j0,j1). O(log h log w)"]
+    pub fn query(&self, i0: usize, i1: usize, j0: usize, j1: usize) -> M::T {
+        let mut s = M::id();
+        let mut a = i0 + self.h;
+        let mut b = i1 + self.h;
+        while a < b {
+            if a & 1 == 1 {
+                s = M::op(&s, &self.query_row(a, j0, j1));
+                a += 1;
+            }
+            if b & 1 == 1 {
+                b -= 1;
+                s = M::op(&s, &self.query_row(b, j0, j1));
+            }
+            a >>= 1;
+            b >>= 1;
+        }
+        s
+    }
+}
+
+#[test]
+fn test_seg2d() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    for _ in 0..30 {
+        let h = rng.rand(12) as usize + 1;
+        let w = rng.rand(12) as usize + 1;
+        let mut sum: SEG2D<SUM> = SEG2D::new(h, w);
+        let mut mx: SEG2D<MAX> = SEG2D::new(h, w);
+        let mut v = vec![vec![0; w]; h];
+        for i in 0..h {
+            for j in 0..w {
+                mx.update(i, j, 0);
+            }
+        }
+        for _ in 0..200 {
+            let (i, j) = (rng.rand(h as u64) as usize, rng.rand(w as u64) as usize);
+            let x = rng.rand(100) as i64 - 50;
+            v[i][j] = x;
+            sum.update(i, j, x);
+            mx.update(i, j, x);
+            assert_eq!(sum.get(i, j), x);
+            let (a, b) = (rng.rand(h as u64 + 1) as usize, rng.rand(h as u64 + 1) as usize);
+            let (i0, i1) = (a.min(b), a.max(b));
+            let (a, b) = (rng.rand(w as u64 + 1) as usize, rng.rand(w as u64 + 1) as usize);
+            let (j0, j1) = (a.min(b), a.max(b));
+            let cells = || (i0..i1).flat_map(|i| v[i][j0..j1].iter().cloned());
+            assert_eq!(sum.query(i0, i1, j0, j1), cells().sum::<i64>());
+            assert_eq!(mx.query(i0, i1, j0, j1), cells().max().unwrap_or(std::i64::MIN));
+        }
+    }
+}
@@ -0,0 +1,309 @@
+#[snippet = "SegBeats"]
+const BEATS_INF: i64 = std::i64::MAX / 4;
+
+#[snippet = "SegBeats"]
+#[derive(Clone, Copy)]
+struct BeatsNode {
+    sum: i64,
+    // largest, second largest (strictly) and count of the largest
+    max1: i64,
+    max2: i64,
+    maxc: i64,
+    min1: i64,
+    min2: i64,
+    minc: i64,
+    len: i64,
+    add: i64,
+}
+
+#[snippet = "SegBeats"]
+#[allow(dead_code)]
+/// Segment Tree Beats. range chmin, chmax, add and range sum, max, min in amortized O(log^2 n)
+pub struct SegBeats {
+    n: usize,
+    buf: Vec<BeatsNode>,
+}
+
+#[snippet = "SegBeats"]
+#[allow(dead_code)]
+impl SegBeats {
+    pub fn new(xs: &[i64]) -> SegBeats {
+        let mut n = 1;
+        while n < xs.len() {
+            n *= 2;
+        }
+        let empty = BeatsNode {
+            sum: 0,
+            max1: -BEATS_INF,
+            max2: -BEATS_INF,
+            maxc: 0,
+            min1: BEATS_INF,
+            min2: BEATS_INF,
+            minc: 0,
+            len: 0,
+            add: 0,
+        };
+        let mut buf = vec![empty; 2 * n];
+        for i in 0..xs.len() {
+            let x = xs[i];
+            buf[n + i] = BeatsNode {
+                sum: x,
+                max1: x,
+                maxc: 1,
+                min1: x,
+                minc: 1,
+                len: 1,
+                ..empty
+            };
+        }
+        let mut seg = SegBeats { n: n, buf: buf };
+        for k in (1..n).rev() {
+            seg.pull(k);
+        }
+        seg
+    }
+    fn pull(&mut self, k: usize) {
+        let (l, r) = (self.buf[2 * k], self.buf[2 * k + 1]);
+        let t = &mut self.buf[k];
+        t.sum = l.sum + r.sum;
+        t.len = l.len + r.len;
+        if l.max1 > r.max1 {
+            t.max1 = l.max1;
+            t.maxc = l.maxc;
+            t.max2 = l.max2.max(r.max1);
+        } else if l.max1 < r.max1 {
+            t.max1 = r.max1;
+            t.maxc = r.maxc;
+            t.max2 = l.max1.max(r.max2);
+        } else {
+            t.max1 = l.max1;
+            t.maxc = l.maxc + r.maxc;
+            t.max2 = l.max2.max(r.max2);
+        }
+        if l.min1 < r.min1 {
+            t.min1 = l.min1;
+            t.minc = l.minc;
+            t.min2 = l.min2.min(r.min1);
+        } else if l.min1 > r.min1 {
+            t.min1 = r.min1;
+            t.minc = r.minc;
+            t.min2 = l.min1.min(r.min2);
+        } else {
+            t.min1 = l.min1;
+            t.minc = l.minc + r.minc;
+            t.min2 = l.min2.min(r.min2);
+        }
+    }
+    // max2 < x < max1
+    fn apply_chmin(&mut self, k: usize, x: i64) {
+        let t = &mut self.buf[k];
+        t.sum += (x - t.max1) * t.maxc;
+        if t.max1 == t.min1 {
+            t.min1 = x;
+        } else if t.max1 == t.min2 {
+            t.min2 = x;
+        }
+        t.max1 = x;
+    }
+    // min1 < x < min2
+    fn apply_chmax(&mut self, k: usize, x: i64) {
+        let t = &mut self.buf[k];
+        t.sum += (x - t.min1) * t.minc;
+        if t.min1 == t.max1 {
+            t.max1 = x;
+        } else if t.min1 == t.max2 {
+            t.max2 = x;
+        }
+        t.min1 = x;
+    }
+    fn apply_add(&mut self, k: usize, x: i64) {
+        let t = &mut self.buf[k];
+        if t.len == 0 {
+            return;
+        }
+        t.sum += x * t.len;
+        t.max1 += x;
+        if t.max2 != -BEATS_INF {
+            t.max2 += x;
+        }
+        t.min1 += x;
+        if t.min2 != BEATS_INF {
+            t.min2 += x;
+        }
+        t.add += x;
+    }
+    fn push(&mut self, k: usize) {
+        let add = self.buf[k].add;
+        if add != 0 {
+            self.apply_add(2 * k, add);
+            self.apply_add(2 * k + 1, add);
+            self.buf[k].add = 0;
+        }
+        let (max1, min1) = (self.buf[k].max1, self.buf[k].min1);
+        for c in 2 * k..2 * k + 2 {
+            if max1 < self.buf[c].max1 {
+                self.apply_chmin(c, max1);
+            }
+            if min1 > self.buf[c].min1 {
+                self.apply_chmax(c, min1);
+            }
+        }
+    }
+    fn do_chmin(&mut self, k: usize, l: usize, r: usize, a: usize, b: usize, x: i64) {
+        if b <= l || r <= a || self.buf[k].max1 <= x {
+            return;
+        }
+        if a <= l && r <= b && self.buf[k].max2 < x {
+            self.apply_chmin(k, x);
+            return;
+        }
+        self.push(k);
+        let m = (l + r) / 2;
+        self.do_chmin(2 * k, l, m, a, b, x);
+        self.do_chmin(2 * k + 1, m, r, a, b, x);
+        self.pull(k);
+    }
+    fn do_chmax(&mut self, k: usize, l: usize, r: usize, a: usize, b: usize, x: i64) {
+        if b <= l || r <= a || self.buf[k].min1 >= x {
+            return;
+        }
+        if a <= l && r <= b && self.buf[k].min2 > x {
+            self.apply_chmax(k, x);
+            return;
+        }
+        self.push(k);
+        let m = (l + r) / 2;
+        self.do_chmax(2 * k, l, m, a, b, x);
+        self.do_chmax(2 * k + 1, m, r, a, b, x);
+        self.pull(k);
+    }
+    fn do_add(&mut self, k: usize, l: usize, r: usize, a: usize, b: usize, x: i64) {
+        if b <= l || r <= a {
+            return;
+        }
+        if a <= l && r <= b {
+            self.apply_add(k, x);
+            return;
+        }
+        self.push(k);
+        let m = (l + r) / 2;
+        self.do_add(2 * k, l, m, a, b, x);
+        self.do_add(2 * k + 1, m, r, a, b, x);
+        self.pull(k);
+    }
+    #[doc = "x_i = min(x_i, x) for i in [a, b)"]
+    pub fn chmin(&mut self, a: usize, b: usize, x: i64) {
+        let n = self.n;
+        self.do_chmin(1, 0, n, a, b, x);
+    }
+    #[doc = "x_i = max(x_i, x) for i in [a, b)"]
+    pub fn chmax(&mut self, a: usize, b: usize, x: i64) {
+        let n = self.n;
+        self.do_chmax(1, 0, n, a, b, x);
+    }
+    #[doc = "x_i += x for i in [a, b)"]
+    pub fn add(&mut self, a: usize, b: usize, x: i64) {
+        let n = self.n;
+        self.do_add(1, 0, n, a, b, x);
+    }
+    // fold op over the nodes covering [a, b)
+    fn fold<F: Fn(i64, &BeatsNode) -> i64>(&mut self, k: usize, l: usize, r: usize, a: usize, b: usize, e: i64, op: &F) -> i64 {
+        if b <= l || r <= a {
+            return e;
+        }
+        if a <= l && r <= b {
+            return op(e, &self.buf[k]);
+        }
+        self.push(k);
+        let m = (l + r) / 2;
+        let x = self.fold(2 * k, l, m, a, b, e, op);
+        self.fold(2 * k + 1, m, r, a, b, x, op)
+    }
+    #[doc = "[a, b)"]
+    pub fn query_sum(&mut self, a: usize, b: usize) -> i64 {
+        let n = self.n;
+        self.fold(1, 0, n, a, b, 0, &|s, t| s + t.sum)
+    }
+    #[doc = "[a, b). -inf if empty"]
+    pub fn query_max(&mut self, a: usize, b: usize) -> i64 {
+        let n = self.n;
+        self.fold(1, 0, n, a, b, -BEATS_INF, &|s, t| s.max(t.max1))
+    }
+    #[doc = "[a, b). inf if empty"]
+    pub fn query_min(&mut self, a: usize, b: usize) -> i64 {
+        let n = self.n;
+        self.fold(1, 0, n, a, b, BEATS_INF, &|s, t| s.min(t.min1))
+    }
+}
+
+#[test]
+fn test_seg_beats() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    for _ in 0..100 {
+        let n = rng.rand(50) as usize + 1;
+        let mut v: Vec<i64> = (0..n).map(|_| rng.rand(201) as i64 - 100).collect();
+        let mut seg = SegBeats::new(&v);
+        for _ in 0..300 {
+            let a = rng.rand(n as u64 + 1) as usize;
+            let b = rng.rand(n as u64 + 1) as usize;
+            let (a, b) = (a.min(b), a.max(b));
+            let x = rng.rand(201) as i64 - 100;
+            match rng.rand(4) {
+                0 => {
+                    seg.chmin(a, b, x);
+                    for i in a..b {
+                        v[i] = v[i].min(x);
+                    }
+                }
+                1 => {
+                    seg.chmax(a, b, x);
+                    for i in a..b {
+                        v[i] = v[i].max(x);
+                    }
+                }
+                2 => {
+                    seg.add(a, b, x);
+                    for i in a..b {
+                        v[i] += x;
+                    }
+                }
+                _ => {
+                    assert_eq!(seg.query_sum(a, b), v[a..b].iter().sum::<i64>());
+                    assert_eq!(seg.query_max(a, b), v[a..b].iter().cloned().max().unwrap_or(-BEATS_INF));
+                    assert_eq!(seg.query_min(a, b), v[a..b].iter().cloned().min().unwrap_or(BEATS_INF));
+                }
+            }
+        }
+        for i in 0..n {
+            assert_eq!(seg.query_sum(i, i + 1), v[i]);
+        }
+    }
+}
+
+#[bench]
+fn bench_seg_beats(b: &mut test::Bencher) {
+    use crate::xorshift::Xorshift;
+    let n = 200_000;
+    let mut rng = Xorshift::new();
+    let xs: Vec<i64> = (0..n).map(|_| rng.rand(1_000_000_000) as i64).collect();
+    let mut ops = vec![];
+    for _ in 0..n {
+        let l = rng.rand(n as u64) as usize;
+        let r = rng.rand(n as u64) as usize;
+        ops.push((rng.rand(4), l.min(r), l.max(r) + 1, rng.rand(1_000_000_000) as i64));
+    }
+    b.iter(|| {
+        let mut seg = SegBeats::new(&xs);
+        let mut s = 0;
+        for &(t, l, r, x) in &ops {
+            match t {
+                0 => seg.chmin(l, r, x),
+                1 => seg.chmax(l, r, x),
+                2 => seg.add(l, r, x - 500_000_000),
+                _ => s ^= seg.query_sum(l, r),
+            }
+        }
+        s
+    });
+}
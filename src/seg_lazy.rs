@@ -100,6 +100,180 @@ impl <T: SEGImpl> SEG<T> {
     }
 }
 
+// Iterative version. lazy[k] is pending for the children of k only, and data[k] is always up to date.
+// values live in Cells so that query can push lazies through &self.
+#[snippet = "SEG_LAZY_ITER"]
+struct SEGIter<T: SEGImpl> {
+    n: usize,
+    log: usize,
+    data: Vec<std::cell::Cell<T::Monoid>>,
+    lazy: Vec<std::cell::Cell<T::OperatorMonoid>>,
+    weight: Vec<usize>,
+}
+
+#[snippet = "SEG_LAZY_ITER"]
+#[allow(dead_code)]
+impl <T: SEGImpl> SEGIter<T> {
+    fn new(init: T::Monoid, n: usize) -> SEGIter<T> {
+        let mut log = 0;
+        while 1 << log < n { log += 1; }
+        let m = 1 << log;
+        let seg = SEGIter {
+            n: m,
+            log: log,
+            data: vec![std::cell::Cell::new(init); 2*m],
+            lazy: vec![std::cell::Cell::new(T::om0()); m],
+            weight: Self::mk_weight(&vec![1;n], m),
+        };
+        for k in (1..m).rev() {
+            seg.pull(k);
+        }
+        seg
+    }
+    fn mk_weight(xs: &[usize], m: usize) -> Vec<usize> {
+        let mut res = vec![0;2*m];
+        for i in 0..xs.len() {
+            res[m+i] = xs[i];
+        }
+        for k in (1..m).rev() {
+            res[k] = res[2*k]+res[2*k+1];
+        }
+        res
+    }
+    fn set_weight(&mut self, weight: &[usize]) {
+        self.weight = Self::mk_weight(weight, self.n);
+    }
+    fn pull(&self, k: usize) {
+        self.data[k].set(T::f(self.data[2*k].get(), self.data[2*k+1].get()));
+    }
+    fn all_apply(&self, k: usize, x: T::OperatorMonoid) {
+        self.data[k].set(T::g(self.data[k].get(), x, self.weight[k]));
+        if k < self.n {
+            self.lazy[k].set(T::h(self.lazy[k].get(), x));
+        }
+    }
+    fn push(&self, k: usize) {
+        let x = self.lazy[k].get();
+        if x != T::om0() {
+            self.all_apply(2*k, x);
+            self.all_apply(2*k+1, x);
+            self.lazy[k].set(T::om0());
+        }
+    }
+    fn push_bounds(&self, l: usize, r: usize) {
+        for i in (1..self.log+1).rev() {
+            if (l >> i) << i != l { self.push(l >> i); }
+            if (r >> i) << i != r { self.push((r-1) >> i); }
+        }
+    }
+    // [a,b)
+    fn update(&mut self, a: usize, b: usize, x: T::OperatorMonoid) -> T::Monoid {
+        if a < b {
+            let (l, r) = (a + self.n, b + self.n);
+            self.push_bounds(l, r);
+            let (mut a, mut b) = (l, r);
+            while a < b {
+                if a & 1 == 1 { self.all_apply(a, x); a += 1; }
+                if b & 1 == 1 { b -= 1; self.all_apply(b, x); }
+                a >>= 1;
+                b >>= 1;
+            }
+            for i in 1..self.log+1 {
+                if (l >> i) << i != l { self.pull(l >> i); }
+                if (r >> i) << i != r { self.pull((r-1) >> i); }
+            }
+        }
+        self.data[1].get()
+    }
+    // [a,b)
+    fn query(&self, a: usize, b: usize) -> T::Monoid {
+        if a >= b {
+            return T::m0();
+        }
+        let (mut a, mut b) = (a + self.n, b + self.n);
+        self.push_bounds(a, b);
+        let mut sl = T::m0();
+        let mut sr = T::m0();
+        while a < b {
+            if a & 1 == 1 { sl = T::f(sl, self.data[a].get()); a += 1; }
+            if b & 1 == 1 { b -= 1; sr = T::f(self.data[b].get(), sr); }
+            a >>= 1;
+            b >>= 1;
+        }
+        T::f(sl, sr)
+    }
+}
+
+#[cfg(test)]
+fn check_iter_vs_rec<T: SEGImpl>(init: T::Monoid, ops: &[(bool, usize, usize, T::OperatorMonoid)], n: usize)
+    where T::Monoid: PartialEq + std::fmt::Debug {
+    let mut rec: SEG<T> = SEG::new(init, n);
+    let mut it: SEGIter<T> = SEGIter::new(init, n);
+    for &(upd, a, b, x) in ops {
+        if upd {
+            rec.update(a, b, x);
+            it.update(a, b, x);
+        } else {
+            assert_eq!(it.query(a, b), rec.query(a, b));
+        }
+    }
+}
+
+#[cfg(test)]
+fn mk_ops(n: usize, q: usize, lo: i64, hi: i64) -> Vec<(bool, usize, usize, i64)> {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    (0..q).map(|_| {
+        let a = rng.rand(n as u64) as usize;
+        let b = rng.rand(n as u64) as usize;
+        let x = lo + rng.rand((hi - lo) as u64) as i64;
+        (rng.rand(2) == 0, a.min(b), a.max(b) + 1, x)
+    }).collect()
+}
+
+#[test]
+fn test_seg_lazy_iter() {
+    for &n in &[1, 2, 7, 8, 33] {
+        // update values are nonzero as 0 is om0 for the RUQ presets
+        let ops = mk_ops(n, 2000, 1, 1000);
+        check_iter_vs_rec::<MAX_RUQ>(MAX_RUQ::m0(), &ops, n);
+        check_iter_vs_rec::<MIN_RUQ>(MIN_RUQ::m0(), &ops, n);
+        check_iter_vs_rec::<SUM_RUQ>(SUM_RUQ::m0(), &ops, n);
+        let ops = mk_ops(n, 2000, -1000, 1000);
+        check_iter_vs_rec::<SUM_RAQ>(0, &ops, n);
+        check_iter_vs_rec::<MAX_RAQ>(0, &ops, n);
+        check_iter_vs_rec::<MIN_RAQ>(0, &ops, n);
+    }
+}
+
+#[bench]
+fn bench_seg_lazy_recursive(b: &mut test::Bencher) {
+    let n = 100_000;
+    let ops = mk_ops(n, 1_000_000, -1000, 1000);
+    b.iter(|| {
+        let mut seg: SEG<SUM_RAQ> = SEG::new(0, n);
+        let mut acc = 0;
+        for &(upd, a, b, x) in &ops {
+            if upd { seg.update(a, b, x); } else { acc ^= seg.query(a, b); }
+        }
+        acc
+    })
+}
+
+#[bench]
+fn bench_seg_lazy_iterative(b: &mut test::Bencher) {
+    let n = 100_000;
+    let ops = mk_ops(n, 1_000_000, -1000, 1000);
+    b.iter(|| {
+        let mut seg: SEGIter<SUM_RAQ> = SEGIter::new(0, n);
+        let mut acc = 0;
+        for &(upd, a, b, x) in &ops {
+            if upd { seg.update(a, b, x); } else { acc ^= seg.query(a, b); }
+        }
+        acc
+    })
+}
+
 #[snippet = "SEG_LAZY_MAX_RUQ"]
 struct MAX_RUQ;
 #[snippet = "SEG_LAZY_MAX_RUQ"]
@@ -302,3 +476,391 @@ fn test_rmq_raq() { // DSL_2_H
     assert_eq!(seg.query(0,6),-1);
 }
 
+
+use crate::seg::Monoid;
+
+#[snippet = "LazySEG"]
+#[allow(dead_code)]
+/// Monoid acting on another monoid: apply(f, op(x, y)) == op(apply(f, x), apply(f, y))
+pub trait MapMonoid {
+    type M: Monoid;
+    type F: Clone;
+    fn id_map() -> Self::F;
+    #[doc = "f after g"]
+    fn compose(f: &Self::F, g: &Self::F) -> Self::F;
+    fn apply(f: &Self::F, x: &<Self::M as Monoid>::T) -> <Self::M as Monoid>::T;
+}
+
+#[snippet = "LazySEG"]
+#[allow(dead_code)]
+pub struct LazySEG<A: MapMonoid> {
+    n: usize,
+    size: usize,
+    log: usize,
+    buf: Vec<<A::M as Monoid>::T>,
+    lazy: Vec<A::F>,
+}
+
+#[snippet = "LazySEG"]
+#[allow(dead_code)]
+impl<A: MapMonoid> LazySEG<A> {
+    pub fn new(n: usize) -> LazySEG<A> {
+        Self::from_vec(vec![<A::M as Monoid>::id(); n])
+    }
+    #[doc = "O(n)"]
+    pub fn from_vec(v: Vec<<A::M as Monoid>::T>) -> LazySEG<A> {
+        let size = v.len();
+        let mut log = 0;
+        while 1 << log < size {
+            log += 1;
+        }
+        let n = 1 << log;
+        let mut buf = vec![<A::M as Monoid>::id(); 2 * n];
+        for (i, x) in v.into_iter().enumerate() {
+            buf[n + i] = x;
+        }
+        let mut seg = LazySEG {
+            n: n,
+            size: size,
+            log: log,
+            buf: buf,
+            lazy: vec![A::id_map(); n],
+        };
+        for k in (1..n).rev() {
+            seg.pull(k);
+        }
+        seg
+    }
+    fn pull(&mut self, k: usize) {
+        self.buf[k] = <A::M as Monoid>::op(&self.buf[2 * k], &self.buf[2 * k + 1]);
+    }
+    fn all_apply(&mut self, k: usize, f: &A::F) {
+        self.buf[k] = A::apply(f, &self.buf[k]);
+        if k < self.n {
+            self.lazy[k] = A::compose(f, &self.lazy[k]);
+        }
+    }
+    fn push(&mut self, k: usize) {
+        let f = std::mem::replace(&mut self.lazy[k], A::id_map());
+        self.all_apply(2 * k, &f);
+        self.all_apply(2 * k + 1, &f);
+    }
+    // push every lazy value above the leaves l and r - 1 that a query on [l, r) does not cover
+    fn push_bounds(&mut self, l: usize, r: usize) {
+        for i in (1..self.log + 1).rev() {
+            if (l >> i) << i != l {
+                self.push(l >> i);
+            }
+            if (r >> i) << i != r {
+                self.push((r - 1) >> i);
+            }
+        }
+    }
+    pub fn set(&mut self, k: usize, x: <A::M as Monoid>::T) {
+        let k = k + self.n;
+        for i in (1..self.log + 1).rev() {
+            self.push(k >> i);
+        }
+        self.buf[k] = x;
+        for i in 1..self.log + 1 {
+            self.pull(k >> i);
+        }
+    }
+    pub fn get(&mut self, k: usize) -> <A::M as Monoid>::T {
+        let k = k + self.n;
+        for i in (1..self.log + 1).rev() {
+            self.push(k >> i);
+        }
+        self.buf[k].clone()
+    }
+    #[doc = "[l, r). O(log n)"]
+    pub fn query(&mut self, l: usize, r: usize) -> <A::M as Monoid>::T {
+        if l == r {
+            return <A::M as Monoid>::id();
+        }
+        let (mut l, mut r) = (l + self.n, r + self.n);
+        self.push_bounds(l, r);
+        let mut sl = <A::M as Monoid>::id();
+        let mut sr = <A::M as Monoid>::id();
+        while l < r {
+            if l & 1 == 1 {
+                sl = <A::M as Monoid>::op(&sl, &self.buf[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                sr = <A::M as Monoid>::op(&self.buf[r], &sr);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        <A::M as Monoid>::op(&sl, &sr)
+    }
+    #[doc = "apply f to [l, r). O(log n)"]
+    pub fn update(&mut self, l: usize, r: usize, f: A::F) {
+        if l == r {
+            return;
+        }
+        let (l, r) = (l + self.n, r + self.n);
+        self.push_bounds(l, r);
+        {
+            let (mut l, mut r) = (l, r);
+            while l < r {
+                if l & 1 == 1 {
+                    self.all_apply(l, &f);
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    self.all_apply(r, &f);
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        for i in 1..self.log + 1 {
+            if (l >> i) << i != l {
+                self.pull(l >> i);
+            }
+            if (r >> i) << i != r {
+                self.pull((r - 1) >> i);
+            }
+        }
+    }
+    #[doc = "max r such that pred(query(l, r)). pred(id) must hold and pred must be monotone. O(log n)"]
+    pub fn max_right<P: Fn(&<A::M as Monoid>::T) -> bool>(&mut self, l: usize, pred: P) -> usize {
+        assert!(pred(&<A::M as Monoid>::id()));
+        if l == self.size {
+            return self.size;
+        }
+        let mut l = l + self.n;
+        for i in (1..self.log + 1).rev() {
+            self.push(l >> i);
+        }
+        let mut sm = <A::M as Monoid>::id();
+        loop {
+            while l % 2 == 0 {
+                l >>= 1;
+            }
+            if !pred(&<A::M as Monoid>::op(&sm, &self.buf[l])) {
+                while l < self.n {
+                    self.push(l);
+                    l *= 2;
+                    let t = <A::M as Monoid>::op(&sm, &self.buf[l]);
+                    if pred(&t) {
+                        sm = t;
+                        l += 1;
+                    }
+                }
+                return l - self.n;
+            }
+            sm = <A::M as Monoid>::op(&sm, &self.buf[l]);
+            l += 1;
+            if l & l.wrapping_neg() == l {
+                return self.size;
+            }
+        }
+    }
+    #[doc = "min l such that pred(query(l, r)). pred(id) must hold and pred must be monotone. O(log n)"]
+    pub fn min_left<P: Fn(&<A::M as Monoid>::T) -> bool>(&mut self, r: usize, pred: P) -> usize {
+        assert!(pred(&<A::M as Monoid>::id()));
+        if r == 0 {
+            return 0;
+        }
+        let mut r = r + self.n;
+        for i in (1..self.log + 1).rev() {
+            self.push((r - 1) >> i);
+        }
+        let mut sm = <A::M as Monoid>::id();
+        loop {
+            r -= 1;
+            while r > 1 && r % 2 == 1 {
+                r >>= 1;
+            }
+            if !pred(&<A::M as Monoid>::op(&self.buf[r], &sm)) {
+                while r < self.n {
+                    self.push(r);
+                    r = 2 * r + 1;
+                    let t = <A::M as Monoid>::op(&self.buf[r], &sm);
+                    if pred(&t) {
+                        sm = t;
+                        r -= 1;
+                    }
+                }
+                return r + 1 - self.n;
+            }
+            sm = <A::M as Monoid>::op(&self.buf[r], &sm);
+            if r & r.wrapping_neg() == r {
+                return 0;
+            }
+        }
+    }
+}
+
+use crate::modint::Mod;
+
+#[snippet = "LazySEG_AFFINE_SUM"]
+#[allow(dead_code)]
+/// (sum, length)
+struct MOD_SUM;
+#[snippet = "LazySEG_AFFINE_SUM"]
+impl Monoid for MOD_SUM {
+    type T = (Mod, Mod);
+    fn id() -> Self::T {
+        (Mod::new(0), Mod::new(0))
+    }
+    fn op(a: &Self::T, b: &Self::T) -> Self::T {
+        (a.0 + b.0, a.1 + b.1)
+    }
+}
+#[snippet = "LazySEG_AFFINE_SUM"]
+#[allow(dead_code)]
+/// x -> a x + b
+struct AFFINE_SUM;
+#[snippet = "LazySEG_AFFINE_SUM"]
+impl MapMonoid for AFFINE_SUM {
+    type M = MOD_SUM;
+    type F = (Mod, Mod);
+    fn id_map() -> Self::F {
+        (Mod::new(1), Mod::new(0))
+    }
+    fn compose(f: &Self::F, g: &Self::F) -> Self::F {
+        (f.0 * g.0, f.0 * g.1 + f.1)
+    }
+    fn apply(f: &Self::F, x: &(Mod, Mod)) -> (Mod, Mod) {
+        (f.0 * x.0 + f.1 * x.1, x.1)
+    }
+}
+
+#[snippet = "LazySEG_ADD_MIN"]
+#[allow(dead_code)]
+struct ADD_MIN_M;
+#[snippet = "LazySEG_ADD_MIN"]
+impl Monoid for ADD_MIN_M {
+    type T = i64;
+    fn id() -> Self::T {
+        std::i64::MAX
+    }
+    fn op(a: &Self::T, b: &Self::T) -> Self::T {
+        std::cmp::min(*a, *b)
+    }
+}
+#[snippet = "LazySEG_ADD_MIN"]
+#[allow(dead_code)]
+struct ADD_MIN;
+#[snippet = "LazySEG_ADD_MIN"]
+impl MapMonoid for ADD_MIN {
+    type M = ADD_MIN_M;
+    type F = i64;
+    fn id_map() -> Self::F {
+        0
+    }
+    fn compose(f: &Self::F, g: &Self::F) -> Self::F {
+        f + g
+    }
+    fn apply(f: &Self::F, x: &i64) -> i64 {
+        if *x == std::i64::MAX { *x } else { x + f }
+    }
+}
+
+#[test]
+fn test_lazy_seg_affine_sum() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    let n = 50;
+    let mut v: Vec<Mod> = (0..n).map(|_| Mod::new(rng.rand(1_000_000_007) as i64)).collect();
+    let mut seg: LazySEG<AFFINE_SUM> = LazySEG::from_vec(v.iter().map(|&x| (x, Mod::new(1))).collect());
+    for _ in 0..1000 {
+        let a = rng.rand(n as u64 + 1) as usize;
+        let b = rng.rand(n as u64 + 1) as usize;
+        let (l, r) = (a.min(b), a.max(b));
+        match rng.rand(3) {
+            0 => {
+                let (c, d) = (Mod::new(rng.rand(1_000_000_007) as i64), Mod::new(rng.rand(1_000_000_007) as i64));
+                seg.update(l, r, (c, d));
+                for i in l..r {
+                    v[i] = c * v[i] + d;
+                }
+            }
+            1 => {
+                let x = Mod::new(rng.rand(100) as i64);
+                if l < n {
+                    seg.set(l, (x, Mod::new(1)));
+                    v[l] = x;
+                    assert_eq!(seg.get(l).0, x);
+                }
+            }
+            _ => {
+                let s = v[l..r].iter().fold(Mod::new(0), |acc, &x| acc + x);
+                assert_eq!(seg.query(l, r), (s, Mod::new((r - l) as i64)));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_lazy_seg_max_right_min_left() {
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    let n = 37;
+    let mut v: Vec<i64> = (0..n).map(|_| rng.rand(100) as i64).collect();
+    let mut seg: LazySEG<ADD_MIN> = LazySEG::from_vec(v.clone());
+    for _ in 0..1000 {
+        let a = rng.rand(n as u64 + 1) as usize;
+        let b = rng.rand(n as u64 + 1) as usize;
+        let (l, r) = (a.min(b), a.max(b));
+        if rng.rand(2) == 0 {
+            let x = rng.rand(21) as i64 - 10;
+            seg.update(l, r, x);
+            for i in l..r {
+                v[i] += x;
+            }
+        } else {
+            let w = rng.rand(150) as i64 - 20;
+            let mr = (l..=n).filter(|&k| v[l..k].iter().all(|&x| x >= w)).max().unwrap();
+            assert_eq!(seg.max_right(l, |&m| m >= w), mr);
+            let ml = (0..=r).filter(|&k| v[k..r].iter().all(|&x| x >= w)).min().unwrap();
+            assert_eq!(seg.min_left(r, |&m| m >= w), ml);
+            assert_eq!(seg.query(l, r), v[l..r].iter().cloned().min().unwrap_or(std::i64::MAX));
+        }
+    }
+}
+
+#[test]
+fn test_lazy_seg_non_copy() {
+    // strings under concatenation, shifted like a Caesar cipher
+    struct CONCAT;
+    impl Monoid for CONCAT {
+        type T = String;
+        fn id() -> String {
+            String::new()
+        }
+        fn op(a: &String, b: &String) -> String {
+            format!("{}{}", a, b)
+        }
+    }
+    struct SHIFT;
+    impl MapMonoid for SHIFT {
+        type M = CONCAT;
+        type F = u8;
+        fn id_map() -> u8 {
+            0
+        }
+        fn compose(f: &u8, g: &u8) -> u8 {
+            (f + g) % 26
+        }
+        fn apply(f: &u8, x: &String) -> String {
+            x.bytes().map(|c| ((c - b'a' + f) % 26 + b'a') as char).collect()
+        }
+    }
+    let mut seg: LazySEG<SHIFT> = LazySEG::from_vec("abcdef".chars().map(|c| c.to_string()).collect());
+    seg.update(1, 4, 1);
+    assert_eq!(seg.query(0, 6), "acdeef");
+    seg.update(3, 6, 25);
+    assert_eq!(seg.query(2, 6), "ddde");
+    seg.set(0, "z".to_string());
+    seg.update(0, 2, 2);
+    assert_eq!(seg.query(0, 6), "beddde");
+    assert_eq!(seg.max_right(0, |s| !s.contains('e')), 1);
+    assert_eq!(seg.min_left(6, |s| !s.contains('b')), 1);
+}
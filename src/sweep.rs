@@ -0,0 +1,456 @@
+use crate::coord_compression::CoordCompression;
+use crate::geometry::Point;
+use std::cmp::Ordering;
+
+// Bentley-Ottmann in exact arithmetic (de Berg et al. chapter 2).
+// events are visited in (x, y) order, which handles vertical segments as if the sweep line were slightly rotated.
+// intersection points are rationals: with |coordinate| <= 1e9 every numerator fits in i128,
+// and comparing two fractions never multiplies them (cmp_frac expands them as continued fractions).
+
+#[snippet = "bentley_ottmann"]
+// a/b <=> c/d for b, d > 0
+fn cmp_frac(a: i128, b: i128, c: i128, d: i128) -> Ordering {
+    let (mut a, mut b, mut c, mut d) = (a, b, c, d);
+    let mut rev = false;
+    loop {
+        let q1 = a.div_euclid(b);
+        let q2 = c.div_euclid(d);
+        let o = if q1 != q2 {
+            Some(q1.cmp(&q2))
+        } else {
+            let r1 = a - q1 * b;
+            let r2 = c - q2 * d;
+            if r1 == 0 || r2 == 0 {
+                Some(r1.cmp(&r2))
+            } else {
+                // r1/b <=> r2/d is d/r2 <=> b/r1
+                a = b;
+                b = r1;
+                c = d;
+                d = r2;
+                None
+            }
+        };
+        if let Some(o) = o {
+            return if rev { o.reverse() } else { o };
+        }
+        rev = !rev;
+    }
+}
+
+#[snippet = "bentley_ottmann"]
+#[derive(Clone, Copy, Debug)]
+struct Frac(i128, i128);
+
+#[snippet = "bentley_ottmann"]
+impl Frac {
+    fn int(x: i128) -> Frac {
+        Frac(x, 1)
+    }
+}
+
+#[snippet = "bentley_ottmann"]
+impl Ord for Frac {
+    fn cmp(&self, other: &Frac) -> Ordering {
+        cmp_frac(self.0, self.1, other.0, other.1)
+    }
+}
+#[snippet = "bentley_ottmann"]
+impl PartialOrd for Frac {
+    fn partial_cmp(&self, other: &Frac) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+#[snippet = "bentley_ottmann"]
+impl PartialEq for Frac {
+    fn eq(&self, other: &Frac) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+#[snippet = "bentley_ottmann"]
+impl Eq for Frac {}
+
+#[snippet = "bentley_ottmann"]
+struct Sweep {
+    // a < b in (x, y) order
+    segs: Vec<(Point<i128>, Point<i128>)>,
+    // status: implicit treap of segment ids in the order along the sweep line. node 0 is null
+    root: usize,
+    seg: Vec<usize>,
+    ch: Vec<[usize; 2]>,
+    size: Vec<usize>,
+    pri: Vec<u64>,
+    rng: crate::xorshift::Xorshift,
+    // event point -> segments starting there
+    events: std::collections::BTreeMap<(Frac, Frac), Vec<usize>>,
+}
+
+#[snippet = "bentley_ottmann"]
+impl Sweep {
+    fn dir(&self, i: usize) -> Point<i128> {
+        self.segs[i].1 - self.segs[i].0
+    }
+    // y of segment i on the sweep line through p. vertical segments are at p itself.
+    fn y_at(&self, i: usize, p: (Frac, Frac)) -> Frac {
+        let (a, b) = self.segs[i];
+        let dx = b.0 - a.0;
+        if dx == 0 {
+            return p.1;
+        }
+        let Frac(xn, xd) = p.0;
+        Frac(a.1 * dx * xd + (xn - a.0 * xd) * (b.1 - a.1), dx * xd)
+    }
+    // order just after the event point for segments through it: by slope, vertical last
+    fn cmp_slope(&self, i: usize, j: usize) -> Ordering {
+        0.cmp(&self.dir(i).cross(self.dir(j))).then(i.cmp(&j))
+    }
+    fn ends_at(&self, i: usize, p: (Frac, Frac)) -> bool {
+        let b = self.segs[i].1;
+        p == (Frac::int(b.0), Frac::int(b.1))
+    }
+    fn alloc(&mut self, i: usize) -> usize {
+        self.seg.push(i);
+        self.ch.push([0, 0]);
+        self.size.push(1);
+        self.pri.push(self.rng.next());
+        self.seg.len() - 1
+    }
+    fn pull(&mut self, t: usize) {
+        let [l, r] = self.ch[t];
+        self.size[t] = 1 + self.size[l] + self.size[r];
+    }
+    fn merge(&mut self, a: usize, b: usize) -> usize {
+        if a == 0 || b == 0 {
+            return a + b;
+        }
+        if self.pri[a] > self.pri[b] {
+            let r = self.ch[a][1];
+            self.ch[a][1] = self.merge(r, b);
+            self.pull(a);
+            a
+        } else {
+            let l = self.ch[b][0];
+            self.ch[b][0] = self.merge(a, l);
+            self.pull(b);
+            b
+        }
+    }
+    // (first k, rest)
+    fn split(&mut self, t: usize, k: usize) -> (usize, usize) {
+        if t == 0 {
+            return (0, 0);
+        }
+        let [l, r] = self.ch[t];
+        if k <= self.size[l] {
+            let (a, b) = self.split(l, k);
+            self.ch[t][0] = b;
+            self.pull(t);
+            (a, t)
+        } else {
+            let (a, b) = self.split(r, k - self.size[l] - 1);
+            self.ch[t][1] = a;
+            self.pull(t);
+            (t, b)
+        }
+    }
+    fn collect(&self, t: usize, out: &mut Vec<usize>) {
+        if t != 0 {
+            self.collect(self.ch[t][0], out);
+            out.push(self.seg[t]);
+            self.collect(self.ch[t][1], out);
+        }
+    }
+    fn len(&self) -> usize {
+        self.size[self.root]
+    }
+    // k-th segment of status
+    fn get(&self, k: usize) -> usize {
+        let (mut t, mut k) = (self.root, k);
+        loop {
+            let l = self.ch[t][0];
+            if k < self.size[l] {
+                t = l;
+            } else if k == self.size[l] {
+                return self.seg[t];
+            } else {
+                k -= self.size[l] + 1;
+                t = self.ch[t][1];
+            }
+        }
+    }
+    // first index of status whose y at p is (>= if !strict else >) p.y
+    fn lower_bound(&self, p: (Frac, Frac), strict: bool) -> usize {
+        let (mut t, mut res) = (self.root, 0);
+        while t != 0 {
+            let o = self.y_at(self.seg[t], p).cmp(&p.1);
+            if o == Ordering::Less || strict && o == Ordering::Equal {
+                res += self.size[self.ch[t][0]] + 1;
+                t = self.ch[t][1];
+            } else {
+                t = self.ch[t][0];
+            }
+        }
+        res
+    }
+    // replace status[lo, hi) by ins and return the old segments there
+    fn splice(&mut self, lo: usize, hi: usize, ins: &[usize]) -> Vec<usize> {
+        let root = self.root;
+        let (l, rest) = self.split(root, lo);
+        let (m, r) = self.split(rest, hi - lo);
+        let mut old = vec![];
+        self.collect(m, &mut old);
+        let mut m = 0;
+        for &i in ins {
+            let t = self.alloc(i);
+            m = self.merge(m, t);
+        }
+        let lm = self.merge(l, m);
+        self.root = self.merge(lm, r);
+        old
+    }
+    fn find_event(&mut self, i: usize, j: usize, p: (Frac, Frac)) {
+        let (a, b) = self.segs[i];
+        let (c, d) = self.segs[j];
+        let (r, s) = (b - a, d - c);
+        let den = r.cross(s);
+        if den == 0 || !crate::geometry::segments_intersect(a, b, c, d) {
+            return;
+        }
+        let num = (c - a).cross(s);
+        let (num, den) = if den < 0 { (-num, -den) } else { (num, den) };
+        let q = (Frac(a.0 * den + r.0 * num, den), Frac(a.1 * den + r.1 * num, den));
+        if q > p {
+            self.events.entry(q).or_insert(vec![]);
+        }
+    }
+}
+
+#[snippet = "bentley_ottmann"]
+#[allow(dead_code)]
+#[doc = "all pairs (i, j), i < j, of segments sharing at least one point. endpoints count, |coordinate| <= 1e9. O((n + k) log n)"]
+pub fn segment_intersections(segs: &[(Point<i64>, Point<i64>)]) -> Vec<(usize, usize)> {
+    let mut sw = Sweep {
+        segs: vec![],
+        root: 0,
+        seg: vec![0],
+        ch: vec![[0, 0]],
+        size: vec![0],
+        pri: vec![0],
+        rng: crate::xorshift::Xorshift::new(),
+        events: std::collections::BTreeMap::new(),
+    };
+    for (i, &(a, b)) in segs.iter().enumerate() {
+        let a = Point(a.0 as i128, a.1 as i128);
+        let b = Point(b.0 as i128, b.1 as i128);
+        let (a, b) = if (a.0, a.1) <= (b.0, b.1) { (a, b) } else { (b, a) };
+        sw.segs.push((a, b));
+        sw.events.entry((Frac::int(a.0), Frac::int(a.1))).or_insert(vec![]).push(i);
+        sw.events.entry((Frac::int(b.0), Frac::int(b.1))).or_insert(vec![]);
+    }
+    let mut res = vec![];
+    while let Some(p) = sw.events.keys().next().cloned() {
+        let u = sw.events.remove(&p).unwrap();
+        let lo = sw.lower_bound(p, false);
+        let hi = sw.lower_bound(p, true);
+        let through = sw.splice(lo, hi, &[]);
+        // each pair is reported at its first common point:
+        // a pair started before p is new here unless the two are collinear (then they met earlier)
+        for x in 0..u.len() {
+            for y in x + 1..u.len() {
+                res.push((u[x], u[y]));
+            }
+            for &j in &through {
+                res.push((u[x], j));
+            }
+        }
+        for x in 0..through.len() {
+            for y in x + 1..through.len() {
+                if sw.dir(through[x]).cross(sw.dir(through[y])) != 0 {
+                    res.push((through[x], through[y]));
+                }
+            }
+        }
+        let mut ins: Vec<usize> = through.into_iter().filter(|&i| !sw.ends_at(i, p)).collect();
+        ins.extend(u.into_iter().filter(|&i| !sw.ends_at(i, p)));
+        ins.sort_by(|&i, &j| sw.cmp_slope(i, j));
+        let k = ins.len();
+        sw.splice(lo, lo, &ins);
+        if k == 0 {
+            if 0 < lo && lo < sw.len() {
+                let (i, j) = (sw.get(lo - 1), sw.get(lo));
+                sw.find_event(i, j, p);
+            }
+        } else {
+            if 0 < lo {
+                let (i, j) = (sw.get(lo - 1), sw.get(lo));
+                sw.find_event(i, j, p);
+            }
+            if lo + k < sw.len() {
+                let (i, j) = (sw.get(lo + k - 1), sw.get(lo + k));
+                sw.find_event(i, j, p);
+            }
+        }
+    }
+    for e in &mut res {
+        if e.0 > e.1 {
+            *e = (e.1, e.0);
+        }
+    }
+    res.sort();
+    res
+}
+
+#[snippet = "CoveredLength"]
+#[doc = "total length of the union of intervals [xs[l], xs[r]) added so far. xs must be sorted"]
+pub struct CoveredLength {
+    n: usize,
+    xs: Vec<i64>,
+    cnt: Vec<i32>,
+    len: Vec<i64>,
+}
+
+#[snippet = "CoveredLength"]
+#[allow(dead_code)]
+impl CoveredLength {
+    pub fn new(xs: Vec<i64>) -> CoveredLength {
+        let n = xs.len().max(2) - 1;
+        CoveredLength {
+            n: n,
+            xs: xs,
+            cnt: vec![0; 4 * n],
+            len: vec![0; 4 * n],
+        }
+    }
+    fn do_add(&mut self, a: usize, b: usize, v: i32, k: usize, l: usize, r: usize) {
+        if r <= a || b <= l {
+            return;
+        }
+        if a <= l && r <= b {
+            self.cnt[k] += v;
+        } else {
+            self.do_add(a, b, v, 2 * k, l, (l + r) / 2);
+            self.do_add(a, b, v, 2 * k + 1, (l + r) / 2, r);
+        }
+        self.len[k] = if self.cnt[k] > 0 {
+            self.xs[r] - self.xs[l]
+        } else if r - l == 1 {
+            0
+        } else {
+            self.len[2 * k] + self.len[2 * k + 1]
+        };
+    }
+    // v = +1 to cover [xs[l], xs[r]), -1 to remove a previous cover
+    pub fn add(&mut self, l: usize, r: usize, v: i32) {
+        if l < r {
+            let n = self.n;
+            self.do_add(l, r, v, 1, 0, n);
+        }
+    }
+    pub fn covered(&self) -> i64 {
+        self.len[1]
+    }
+}
+
+#[snippet = "rectangle_union_area"]
+#[allow(dead_code)]
+#[doc = "area of the union of rectangles (x1, y1, x2, y2). O(n log n)"]
+pub fn rectangle_union_area(rs: &[(i64, i64, i64, i64)]) -> i64 {
+    let mut xs = vec![];
+    for &(x1, _, x2, _) in rs {
+        xs.push(x1);
+        xs.push(x2);
+    }
+    let cc = CoordCompression::new(&xs, 0);
+    let mut st = CoveredLength::new((0..cc.len()).map(|i| cc.decompress(i)).collect());
+    let mut es = vec![];
+    for &(x1, y1, x2, y2) in rs {
+        let (l, r) = (cc.compress(x1.min(x2)), cc.compress(x1.max(x2)));
+        es.push((y1.min(y2), 1, l, r));
+        es.push((y1.max(y2), -1, l, r));
+    }
+    es.sort();
+    let mut res = 0;
+    for i in 0..es.len() {
+        if i > 0 {
+            res += st.covered() * (es[i].0 - es[i - 1].0);
+        }
+        let (_, v, l, r) = es[i];
+        st.add(l, r, v);
+    }
+    res
+}
+
+#[test]
+fn test_segment_intersections() {
+    use crate::geometry::segments_intersect;
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    for iter in 0..500 {
+        let n = rng.rand(12) as usize + 1;
+        // a small grid gives plenty of vertical, collinear and touching segments
+        let w = if iter % 2 == 0 { 5 } else { 1000 };
+        let mut p = || Point(rng.rand(w) as i64, rng.rand(w) as i64);
+        let segs: Vec<_> = (0..n).map(|_| (p(), p())).collect();
+        let mut expected = vec![];
+        for i in 0..n {
+            for j in i + 1..n {
+                let (a, b) = segs[i];
+                let (c, d) = segs[j];
+                if segments_intersect(a, b, c, d) {
+                    expected.push((i, j));
+                }
+            }
+        }
+        assert_eq!(segment_intersections(&segs), expected);
+    }
+}
+
+#[test]
+fn test_segment_intersections_large() {
+    use crate::geometry::segments_intersect;
+    use crate::xorshift::Xorshift;
+    let mut rng = Xorshift::new();
+    let n = 1000;
+    let mut p = || Point(rng.rand(2_000_000_001) as i64 - 1_000_000_000, rng.rand(2_000_000_001) as i64 - 1_000_000_000);
+    let segs: Vec<_> = (0..n).map(|_| {
+        let a = p();
+        let d = p();
+        (a, Point(a.0 + d.0 / 50, a.1 + d.1 / 50))
+    }).collect();
+    let mut expected = vec![];
+    for i in 0..n {
+        for j in i + 1..n {
+            let (a, b) = segs[i];
+            let (c, d) = segs[j];
+            if segments_intersect(a, b, c, d) {
+                expected.push((i, j));
+            }
+        }
+    }
+    assert_eq!(segment_intersections(&segs), expected);
+}
+
+#[test]
+fn test_rectangle_union_area() {
+    use crate::xorshift::Xorshift;
+    assert_eq!(rectangle_union_area(&[]), 0);
+    assert_eq!(rectangle_union_area(&[(0, 0, 2, 2), (1, 1, 3, 3)]), 7);
+    assert_eq!(rectangle_union_area(&[(0, 0, 1_000_000_000, 1_000_000_000), (-1_000_000_000, 0, 0, 1_000_000_000)]), 2_000_000_000_000_000_000);
+    let mut rng = Xorshift::new();
+    for _ in 0..200 {
+        let n = rng.rand(8) as usize;
+        let mut rs = vec![];
+        let mut grid = [[false; 10]; 10];
+        for _ in 0..n {
+            let (x1, y1, x2, y2) = (rng.rand(11) as i64, rng.rand(11) as i64, rng.rand(11) as i64, rng.rand(11) as i64);
+            rs.push((x1, y1, x2, y2));
+            for x in x1.min(x2)..x1.max(x2) {
+                for y in y1.min(y2)..y1.max(y2) {
+                    grid[x as usize][y as usize] = true;
+                }
+            }
+        }
+        let expected = grid.iter().map(|r| r.iter().filter(|&&b| b).count() as i64).sum::<i64>();
+        assert_eq!(rectangle_union_area(&rs), expected);
+    }
+}